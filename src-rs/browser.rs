@@ -0,0 +1,120 @@
+//! the optimizer for the web front end.
+//!
+//! cobyla doesn't build for wasm, so `spec.optimizer` falls back to nelder-mead there. the
+//! built-in catalog's ovens are numbered `0..oven_count()`, and `optimize_chunk` does any range
+//! of them, so the front end can hand a chunk to each web worker and merge what comes back
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    Catalog, Constraints, CostBreakdown, Environment, Margin, ObjectiveChoice, OptimizationSpec,
+    Oven,
+};
+
+#[wasm_bindgen]
+extern "C" {
+    // called with how many ovens are done and how many there are, after each one
+    #[wasm_bindgen(typescript_type = "(done: number, total: number) => void")]
+    pub type Progress;
+
+    // Function.prototype.call
+    #[wasm_bindgen(method, js_name = call)]
+    fn call(this: &Progress, this_arg: &JsValue, done: u32, total: u32);
+}
+
+// an oven and its best design, what `Oven::multi_start` found
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[wasm_bindgen(getter_with_clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct OptimizedOven {
+    pub oven: Oven,
+    // m, the same inputs as `graph_data`
+    pub h: f64,
+    pub insulator_thickness: f64,
+    pub reflector_ml: f64,
+    // lower is better
+    pub score: f64,
+    // $
+    pub cost: f64,
+    pub cost_breakdown: CostBreakdown,
+    // °C
+    pub tio: f64,
+    // inside the constraints
+    pub feasible: bool,
+    // how the optimizer finished, see `Status`
+    pub status: String,
+    pub failed: bool,
+    // of the objective, from the best start
+    pub evaluations: u32,
+    // how far inside each constraint it is, negative when it's broken
+    pub constraints: Vec<Margin>,
+}
+
+/// the best design for `oven`
+#[wasm_bindgen]
+pub fn optimize_oven(
+    oven: &Oven,
+    env: &Environment,
+    objective: &ObjectiveChoice,
+    constraints: &Constraints,
+    spec: &OptimizationSpec,
+) -> OptimizedOven {
+    let result = oven.multi_start(&spec.starts(), env, objective, constraints, spec);
+    let best = &result.best;
+
+    OptimizedOven {
+        oven: oven.clone(),
+        h: best.design.h.value(),
+        insulator_thickness: best.design.insulator_thickness.value(),
+        reflector_ml: best.design.reflector_ml,
+        score: best.score,
+        cost: best.design.total_cost(),
+        cost_breakdown: best.design.cost_breakdown(),
+        tio: best.design.predicted_tio(env).as_celsius(),
+        feasible: constraints.feasible(&best.design, env),
+        status: best.status.to_string(),
+        failed: best.failed(),
+        evaluations: best.evaluations,
+        constraints: best.constraints.clone(),
+    }
+}
+
+/// how many ovens the built-in catalog can make
+#[wasm_bindgen]
+pub fn oven_count() -> u32 {
+    Catalog::builtin().choices().len() as u32
+}
+
+/// the built-in catalog's ovens `start..start + count` (cut off at `oven_count()`), each
+/// optimized, in order. `progress` is called after each one
+#[wasm_bindgen]
+pub fn optimize_chunk(
+    start: u32,
+    count: u32,
+    env: &Environment,
+    objective: &ObjectiveChoice,
+    constraints: &Constraints,
+    spec: &OptimizationSpec,
+    progress: Option<Progress>,
+) -> Vec<OptimizedOven> {
+    let choices = Catalog::builtin().choices();
+    let ovens: Vec<Oven> = (start as usize..)
+        .take(count as usize)
+        .map_while(|i| choices.nth(i))
+        .collect();
+    let total = ovens.len() as u32;
+
+    ovens
+        .iter()
+        .enumerate()
+        .map(|(i, oven)| {
+            let optimized = optimize_oven(oven, env, objective, constraints, spec);
+            if let Some(progress) = &progress {
+                progress.call(&JsValue::NULL, i as u32 + 1, total);
+            }
+
+            optimized
+        })
+        .collect()
+}
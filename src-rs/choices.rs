@@ -0,0 +1,292 @@
+//! what each part of an oven can be made of, so the ovens can be narrowed down before any of
+//! them are optimized.
+//!
+//! `Catalog::choices` starts with everything, and each `Filter` keeps or drops names in one
+//! `Category`. inner and outer bodies come from the same materials but are filtered separately
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    AbsorberSpec, BodySpec, Catalog, InsulatorSpec, Oven, ReflectiveSpec, ReflectorType, WindowSpec,
+};
+
+#[derive(Debug, Clone)]
+pub struct Choices {
+    pub absorbers: Vec<AbsorberSpec>,
+    pub windows: Vec<WindowSpec>,
+    pub outer_bodies: Vec<BodySpec>,
+    pub inner_bodies: Vec<BodySpec>,
+    pub insulators: Vec<InsulatorSpec>,
+    pub reflector_types: Vec<ReflectorType>,
+    pub reflective_materials: Vec<ReflectiveSpec>,
+    pub reflector_numbers: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Absorber,
+    Window,
+    OuterBody,
+    InnerBody,
+    Insulator,
+    ReflectorType,
+    ReflectiveMaterial,
+    ReflectorNumber,
+}
+
+/// keeps (or with `exclude`, drops) the choices in `category` called one of `names`. names
+/// don't care about case
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub category: Category,
+    pub names: Vec<String>,
+    pub exclude: bool,
+}
+
+impl Catalog {
+    /// every material in the catalog, for every part
+    pub fn choices(&self) -> Choices {
+        Choices {
+            absorbers: self.absorbers.clone(),
+            windows: self.windows.clone(),
+            outer_bodies: self.body_materials.clone(),
+            inner_bodies: self.body_materials.clone(),
+            insulators: self.insulators.clone(),
+            reflector_types: ReflectorType::variants().to_vec(),
+            reflective_materials: self.reflective_materials.clone(),
+            reflector_numbers: (1..=4).collect(),
+        }
+    }
+}
+
+impl Choices {
+    /// every oven these choices can make
+    pub fn ovens(&self) -> Vec<Oven> {
+        itertools::iproduct![
+            &self.absorbers,
+            &self.windows,
+            &self.outer_bodies,
+            &self.inner_bodies,
+            &self.insulators,
+            &self.reflector_types,
+            &self.reflective_materials,
+            &self.reflector_numbers
+        ]
+        .map(
+            |(
+                abs,
+                window,
+                outer_body,
+                inner_body,
+                insulator,
+                &reflector_type,
+                reflective_material,
+                &reflector_number,
+            )| Oven {
+                abs: abs.clone(),
+                window: window.clone(),
+                outer_body: outer_body.clone(),
+                inner_body: inner_body.clone(),
+                insulator: insulator.clone(),
+                reflector_type,
+                reflective_material: reflective_material.clone(),
+                reflector_number,
+            },
+        )
+        .collect()
+    }
+
+    /// the `i`th oven of `ovens()`, without making the rest of them. none past the end
+    pub fn nth(&self, i: usize) -> Option<Oven> {
+        if i >= self.len() {
+            return None;
+        }
+
+        // `ovens()` goes through the last category fastest
+        let mut genome = [0; 8];
+        let mut rest = i;
+        for (gene, len) in genome.iter_mut().zip(self.lens()).rev() {
+            *gene = rest % len;
+            rest /= len;
+        }
+
+        Some(self.oven(&genome))
+    }
+
+    /// how many ovens these choices can make
+    pub fn len(&self) -> usize {
+        self.lens().iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// how many choices there are for each `Category`, in order
+    pub(crate) fn lens(&self) -> [usize; 8] {
+        [
+            self.absorbers.len(),
+            self.windows.len(),
+            self.outer_bodies.len(),
+            self.inner_bodies.len(),
+            self.insulators.len(),
+            self.reflector_types.len(),
+            self.reflective_materials.len(),
+            self.reflector_numbers.len(),
+        ]
+    }
+
+    /// narrows the choices down with `filter`. a name that isn't a choice (probably a typo), or
+    /// a filter that leaves nothing to pick from, is an error
+    pub fn apply(&mut self, filter: &Filter) -> Result<(), String> {
+        fn keep<T>(
+            choices: &mut Vec<T>,
+            name: impl Fn(&T) -> String,
+            filter: &Filter,
+        ) -> Result<(), String> {
+            let names: Vec<String> = choices.iter().map(|c| name(c).to_lowercase()).collect();
+            for wanted in &filter.names {
+                if !names.contains(&wanted.to_lowercase()) {
+                    return Err(format!(
+                        "no {} called \"{wanted}\", expected one of: {}",
+                        filter.category,
+                        choices.iter().map(&name).collect::<Vec<_>>().join(", ")
+                    ));
+                }
+            }
+
+            choices.retain(|c| {
+                let named = filter
+                    .names
+                    .iter()
+                    .any(|wanted| wanted.to_lowercase() == name(c).to_lowercase());
+                named != filter.exclude
+            });
+            if choices.is_empty() {
+                return Err(format!("that leaves no {} to pick from", filter.category));
+            }
+
+            Ok(())
+        }
+
+        match filter.category {
+            Category::Absorber => keep(&mut self.absorbers, |a| a.to_string(), filter),
+            Category::Window => keep(&mut self.windows, |w| w.to_string(), filter),
+            Category::OuterBody => keep(&mut self.outer_bodies, |b| b.to_string(), filter),
+            Category::InnerBody => keep(&mut self.inner_bodies, |b| b.to_string(), filter),
+            Category::Insulator => keep(&mut self.insulators, |i| i.to_string(), filter),
+            Category::ReflectorType => {
+                keep(&mut self.reflector_types, |t| format!("{t:?}"), filter)
+            }
+            Category::ReflectiveMaterial => {
+                keep(&mut self.reflective_materials, |r| r.to_string(), filter)
+            }
+            Category::ReflectorNumber => {
+                keep(&mut self.reflector_numbers, |n| n.to_string(), filter)
+            }
+        }
+    }
+}
+
+impl Category {
+    pub fn variants() -> &'static [Self] {
+        &[
+            Self::Absorber,
+            Self::Window,
+            Self::OuterBody,
+            Self::InnerBody,
+            Self::Insulator,
+            Self::ReflectorType,
+            Self::ReflectiveMaterial,
+            Self::ReflectorNumber,
+        ]
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Absorber => "absorber",
+            Self::Window => "window",
+            Self::OuterBody => "outer-body",
+            Self::InnerBody => "inner-body",
+            Self::Insulator => "insulator",
+            Self::ReflectorType => "reflector-type",
+            Self::ReflectiveMaterial => "reflective-material",
+            Self::ReflectorNumber => "reflector-number",
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+/// "absorber", "window", "outer-body", "inner-body", "insulator", "reflector-type",
+/// "reflective-material" or "reflector-number"
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::variants()
+            .iter()
+            .find(|c| c.key() == s)
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "unknown category \"{s}\", expected one of: {}",
+                    Self::variants()
+                        .iter()
+                        .map(|c| c.key())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+/// "<category>=<name>[,<name>...]", an include filter
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (category, names) = s
+            .split_once('=')
+            .ok_or_else(|| format!("\"{s}\" should look like <category>=<name>[,<name>...]"))?;
+        let names: Vec<String> = names
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        if names.is_empty() {
+            return Err(format!("\"{s}\" needs at least one name"));
+        }
+
+        Ok(Self {
+            category: category.trim().parse()?,
+            names,
+            exclude: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nth_matches_ovens() {
+        let mut choices = Catalog::builtin().choices();
+        for filter in ["absorber=Black Construction Paper", "inner-body=Cardboard"] {
+            choices.apply(&filter.parse().unwrap()).unwrap();
+        }
+        let ovens = choices.ovens();
+
+        assert_eq!(ovens.len(), choices.len());
+        // `Oven` isn't `PartialEq`, but its debug output has every field
+        for (i, oven) in ovens.iter().enumerate() {
+            assert_eq!(format!("{:?}", choices.nth(i)), format!("{:?}", Some(oven)));
+        }
+        assert!(choices.nth(ovens.len()).is_none());
+    }
+}
@@ -0,0 +1,273 @@
+//! hard limits a design has to meet, as opposed to the objective which only ranks them.
+//!
+//! `Oven::best_design` hands every limit that's set to cobyla as a constraint function, and
+//! designs that still break one afterwards are infeasible and shouldn't be ranked at all
+
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::Path};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    files::{self, positive, LoadError},
+    Design, Environment, Length, Temperature,
+};
+
+/// how far past a limit still counts as meeting it, in the units `Limit::measure` uses. cobyla
+/// only gets constraints right to about this
+const TOLERANCE: f64 = 1e-4;
+
+// any field missing from a JSON/TOML file isn't limited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[wasm_bindgen]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct Constraints {
+    // $
+    #[wasm_bindgen(skip)]
+    pub max_cost: Option<f64>,
+    // m, the outside of the box without the reflectors
+    #[wasm_bindgen(skip)]
+    pub max_outer_width: Option<Length>,
+    #[wasm_bindgen(skip)]
+    pub max_outer_height: Option<Length>,
+    #[wasm_bindgen(skip)]
+    pub min_tio: Option<Temperature>,
+    // kg
+    #[wasm_bindgen(skip)]
+    pub max_weight: Option<f64>,
+}
+
+#[wasm_bindgen]
+impl Constraints {
+    // nothing limited
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[wasm_bindgen]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub enum Limit {
+    // always on, the chamber has to be the design's `volume_target`
+    ChamberVolume,
+    Cost,
+    OuterWidth,
+    OuterHeight,
+    Tio,
+    Weight,
+}
+
+/// a design breaking `limit`, `value` and `bound` are in the units `Limit::measure` uses
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct Violation {
+    pub limit: Limit,
+    pub value: f64,
+    pub bound: f64,
+}
+
+// how far inside `limit` a design ended up, see `Constraints::margin`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[wasm_bindgen]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct Margin {
+    pub limit: Limit,
+    // in the units `Limit::measure` uses, negative when it's broken
+    pub margin: f64,
+}
+
+impl Limit {
+    /// what's being limited, in a unit that makes `TOLERANCE` about right
+    pub fn measure(&self, design: &Design, env: &Environment) -> f64 {
+        match self {
+            Limit::ChamberVolume => design.chamber_volume().as_liters(),
+            Limit::Cost => design.total_cost(),
+            Limit::OuterWidth => design.outer_width().cm(),
+            Limit::OuterHeight => design.outer_height().cm(),
+            Limit::Tio => design.predicted_tio(env).as_celsius(),
+            Limit::Weight => design.total_weight(),
+        }
+    }
+
+    /// `value` from `measure` with its unit
+    pub fn show(&self, value: f64) -> String {
+        match self {
+            Limit::ChamberVolume => format!("{value:.3} L"),
+            Limit::Cost => format!("${value:.2}"),
+            Limit::OuterWidth | Limit::OuterHeight => format!("{value:.2} cm"),
+            Limit::Tio => format!("{value:.2}°C"),
+            Limit::Weight => format!("{value:.3} kg"),
+        }
+    }
+
+    fn is_minimum(&self) -> bool {
+        matches!(self, Limit::Tio)
+    }
+}
+
+impl Constraints {
+    pub fn from_json(s: &str) -> Result<Self, LoadError> {
+        let constraints: Self = files::from_json(s)?;
+        constraints.validate()?;
+
+        Ok(constraints)
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, LoadError> {
+        let constraints: Self = files::from_toml(s)?;
+        constraints.validate()?;
+
+        Ok(constraints)
+    }
+
+    /// picks the format from the file extension, anything that isn't `.toml` is read as JSON
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let constraints: Self = files::load(path)?;
+        constraints.validate()?;
+
+        Ok(constraints)
+    }
+
+    pub fn validate(&self) -> Result<(), LoadError> {
+        let name = "constraints";
+
+        if let Some(max) = self.max_cost {
+            positive(name, "max_cost", max)?;
+        }
+        if let Some(max) = self.max_outer_width {
+            positive(name, "max_outer_width", max.value())?;
+        }
+        if let Some(max) = self.max_outer_height {
+            positive(name, "max_outer_height", max.value())?;
+        }
+        if let Some(min) = self.min_tio {
+            if !min.as_celsius().is_finite() {
+                return Err(LoadError::Invalid(format!(
+                    "{name}: min_tio must be a number, got {}",
+                    min.as_celsius()
+                )));
+            }
+        }
+        if let Some(max) = self.max_weight {
+            positive(name, "max_weight", max)?;
+        }
+
+        Ok(())
+    }
+
+    /// the bound on `limit` in the units `Limit::measure` uses, if it's limited. the chamber
+    /// volume is up to the design, see `bound_for`
+    pub fn bound(&self, limit: Limit) -> Option<f64> {
+        match limit {
+            Limit::ChamberVolume => None,
+            Limit::Cost => self.max_cost,
+            Limit::OuterWidth => self.max_outer_width.map(|l| l.cm()),
+            Limit::OuterHeight => self.max_outer_height.map(|l| l.cm()),
+            Limit::Tio => self.min_tio.map(|t| t.as_celsius()),
+            Limit::Weight => self.max_weight,
+        }
+    }
+
+    /// the limits that are set and need a constraint function. the chamber volume isn't one,
+    /// `Design` gets l and w from it so the optimizer can't change it
+    pub fn active(&self) -> Vec<Limit> {
+        [
+            Limit::Cost,
+            Limit::OuterWidth,
+            Limit::OuterHeight,
+            Limit::Tio,
+            Limit::Weight,
+        ]
+        .into_iter()
+        .filter(|&limit| self.bound(limit).is_some())
+        .collect()
+    }
+
+    /// `bound`, with the chamber volume `design` is supposed to have
+    pub fn bound_for(&self, limit: Limit, design: &Design) -> Option<f64> {
+        match limit {
+            Limit::ChamberVolume => Some(design.volume_target.as_liters()),
+            limit => self.bound(limit),
+        }
+    }
+
+    /// how far inside `limit` the design is, negative when it's broken. this is what cobyla
+    /// keeps >= 0
+    pub fn margin(&self, limit: Limit, design: &Design, env: &Environment) -> f64 {
+        let Some(bound) = self.bound_for(limit, design) else {
+            return f64::INFINITY;
+        };
+        let value = limit.measure(design, env);
+
+        match limit {
+            Limit::ChamberVolume => -(value - bound).abs(),
+            limit if limit.is_minimum() => value - bound,
+            _ => bound - value,
+        }
+    }
+
+    /// every limit the design breaks, empty when it's feasible
+    pub fn violations(&self, design: &Design, env: &Environment) -> Vec<Violation> {
+        std::iter::once(Limit::ChamberVolume)
+            .chain(self.active())
+            // NaN counts as broken
+            .filter(|&limit| {
+                let margin = self.margin(limit, design, env);
+                margin.is_nan() || margin < -TOLERANCE
+            })
+            .map(|limit| Violation {
+                limit,
+                value: limit.measure(design, env),
+                bound: self.bound_for(limit, design).unwrap_or(f64::NAN),
+            })
+            .collect()
+    }
+
+    pub fn feasible(&self, design: &Design, env: &Environment) -> bool {
+        self.violations(design, env).is_empty()
+    }
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::ChamberVolume => write!(f, "chamber volume"),
+            Limit::Cost => write!(f, "cost"),
+            Limit::OuterWidth => write!(f, "outer width"),
+            Limit::OuterHeight => write!(f, "outer height"),
+            Limit::Tio => write!(f, "temperature"),
+            Limit::Weight => write!(f, "weight"),
+        }
+    }
+}
+
+impl Display for Margin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.limit, self.limit.show(self.margin))
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let needs = match self.limit {
+            Limit::ChamberVolume => "has to be",
+            limit if limit.is_minimum() => "has to be at least",
+            _ => "has to be at most",
+        };
+
+        write!(
+            f,
+            "{} is {}, {needs} {}",
+            self.limit,
+            self.limit.show(self.value),
+            self.limit.show(self.bound)
+        )
+    }
+}
+
+#[wasm_bindgen]
+pub fn constraints_from_json(constraints: &str) -> Option<Constraints> {
+    Constraints::from_json(constraints).ok()
+}
@@ -1,69 +1,203 @@
-use crate::Design;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{Area, Design, Volume};
+
+// what each part of a design takes and costs, `total_cost` is the sum of these
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[wasm_bindgen]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct CostBreakdown {
+    // m^2, the same as the inner body
+    pub absorber_area: f64,
+    // $
+    pub absorber_cost: f64,
+    // m^2
+    pub inner_body_area: f64,
+    pub inner_body_cost: f64,
+    // m^3
+    pub insulator_volume: f64,
+    pub insulator_cost: f64,
+    // m^2
+    pub outer_body_area: f64,
+    pub outer_body_cost: f64,
+    // m^2
+    pub window_area: f64,
+    pub window_cost: f64,
+    // m^2, every reflector together
+    pub reflector_area: f64,
+    pub reflector_cost: f64,
+}
+
+#[wasm_bindgen]
+impl CostBreakdown {
+    // $
+    pub fn total(&self) -> f64 {
+        self.absorber_cost
+            + self.inner_body_cost
+            + self.insulator_cost
+            + self.outer_body_cost
+            + self.window_cost
+            + self.reflector_cost
+    }
+}
+
+impl Display for CostBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.total();
+        let parts = [
+            ("absorber", self.absorber_area, "m^2", self.absorber_cost),
+            (
+                "inner body",
+                self.inner_body_area,
+                "m^2",
+                self.inner_body_cost,
+            ),
+            (
+                "insulator",
+                self.insulator_volume,
+                "m^3",
+                self.insulator_cost,
+            ),
+            (
+                "outer body",
+                self.outer_body_area,
+                "m^2",
+                self.outer_body_cost,
+            ),
+            ("window", self.window_area, "m^2", self.window_cost),
+            (
+                "reflectors",
+                self.reflector_area,
+                "m^2",
+                self.reflector_cost,
+            ),
+        ];
+
+        for (part, amount, unit, cost) in parts {
+            // everything free (like a catalog with no prices yet) is 0% of nothing, not NaN
+            let share = if total > 0. { 100. * cost / total } else { 0. };
+            writeln!(
+                f,
+                "  {part:<10}  {amount:>8.5} {unit}  {:>8}  {share:>5.1}%",
+                format!("${cost:.4}"),
+            )?;
+        }
+
+        Ok(())
+    }
+}
 
 impl Design {
     fn absorber_cost(&self) -> f64 {
         let area = self.asb();
 
-        self.absorber.cost_per_m2(area)
+        self.absorber.cost(area)
     }
 
     fn inner_body_cost(&self) -> f64 {
         let area = self.asb();
 
-        self.inner_body.cost_per_m2(area)
+        self.inner_body.cost(area)
     }
 
     fn insulator_cost(&self) -> f64 {
+        self.insulator.cost(self.insulator_volume())
+    }
+
+    pub(crate) fn insulator_volume(&self) -> Volume {
         // inner_volume = (h + i_b_t) * (l_and_w + i_b_t * 2) ^ 2
         // outer_volume = (h + i_b_t + i_t) * (l_and_w + i_b_t * 2 + i_t * 2) ^ 2
         // insulator_volume = outer_volume - inner_volume
 
-        let inner_volume = (self.h + self.inner_body.thickness_cm())
-            * (self.l_and_w + self.inner_body.thickness_cm() * 2.).powi(2);
-        let outer_volume = (self.h + self.inner_body.thickness_cm() + self.insulator_thickness)
-            * (self.l_and_w + self.inner_body.thickness_cm() * 2. + self.insulator_thickness * 2.)
-                .powi(2);
+        let inner_l = self.l_and_w + self.inner_body.thickness() * 2.;
+        let outer_l = inner_l + self.insulator_thickness * 2.;
 
-        let insulator_volume = outer_volume - inner_volume;
+        let inner_volume: Volume = (self.h + self.inner_body.thickness()) * (inner_l * inner_l);
+        let outer_volume: Volume =
+            (self.h + self.inner_body.thickness() + self.insulator_thickness) * (outer_l * outer_l);
 
-        self.insulator.cost_per_m3(insulator_volume)
+        outer_volume - inner_volume
     }
 
     fn outer_body_cost(&self) -> f64 {
+        self.outer_body.cost(self.outer_body_area())
+    }
+
+    pub(crate) fn outer_body_area(&self) -> Area {
         // top side window isn't made out of `outer_body` material
         // surface area - aw
-        let h = self.h + self.inner_body.thickness_cm() + self.insulator_thickness;
-        let l = self.l_and_w
-            + self.inner_body.thickness_cm() * 2.
-            + self.insulator_thickness * 2.
-            + self.outer_body.thickness_cm() * 2.;
-        let w = l.clone();
+        let h = self.h + self.inner_body.thickness() + self.insulator_thickness;
+        let l = self.outer_width();
+        let w = l;
 
-        let surface_area = 2. * h * l + 2. * h * w + 2. * l * w;
+        let surface_area: Area = 2. * (h * l) + 2. * (h * w) + 2. * (l * w);
 
-        self.outer_body.cost_per_m2(surface_area - self.aw())
+        surface_area - self.aw()
     }
 
     fn window_cost(&self) -> f64 {
         let area = self.aw();
 
-        self.window.cost_per_m2(area)
+        self.window.cost(area)
     }
 
     fn reflector_cost(&self) -> f64 {
-        let area = self
-            .reflector_type
-            .area(self.reflector_ml, self.l_and_w, self.reflector_count);
+        self.reflectors.cost(self.reflector_area())
+    }
 
-        self.reflectors.cost_per_m2(area)
+    pub(crate) fn reflector_area(&self) -> Area {
+        self.reflector_type
+            .area(self.reflector_ml, self.l_and_w, self.reflector_count)
     }
 
     pub fn total_cost(&self) -> f64 {
-        self.absorber_cost()
-            + self.inner_body_cost()
-            + self.insulator_cost()
-            + self.outer_body_cost()
-            + self.window_cost()
-            + self.reflector_cost()
+        self.cost_breakdown().total()
+    }
+
+    /// `total_cost` split up by part, with how much of each material it takes
+    pub fn cost_breakdown(&self) -> CostBreakdown {
+        CostBreakdown {
+            absorber_area: self.asb().value(),
+            absorber_cost: self.absorber_cost(),
+            inner_body_area: self.asb().value(),
+            inner_body_cost: self.inner_body_cost(),
+            insulator_volume: self.insulator_volume().value(),
+            insulator_cost: self.insulator_cost(),
+            outer_body_area: self.outer_body_area().value(),
+            outer_body_cost: self.outer_body_cost(),
+            window_area: self.aw().value(),
+            window_cost: self.window_cost(),
+            reflector_area: self.reflector_area().value(),
+            reflector_cost: self.reflector_cost(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_design_has_no_nan() {
+        let breakdown = CostBreakdown {
+            absorber_area: 0.01,
+            absorber_cost: 0.,
+            inner_body_area: 0.01,
+            inner_body_cost: 0.,
+            insulator_volume: 0.001,
+            insulator_cost: 0.,
+            outer_body_area: 0.05,
+            outer_body_cost: 0.,
+            window_area: 0.01,
+            window_cost: 0.,
+            reflector_area: 0.04,
+            reflector_cost: 0.,
+        };
+        let shown = breakdown.to_string();
+
+        assert!(!shown.contains("NaN"), "{shown}");
+        assert_eq!(shown.matches("0.0%").count(), 6, "{shown}");
     }
 }
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::Path, str::FromStr};
+
+use crate::{
+    files::{self, positive, LoadError},
+    ClearSky, DateTime, Design, Environment, Irradiance, Location, SunPosition, Temperature,
+};
+
+/// a local time of day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct ClockTime {
+    pub hour: u32,
+    pub minute: u32,
+}
+
+/// when the oven gets turned to face the sun again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub enum Aiming {
+    /// follows the sun perfectly
+    Tracking,
+    /// aimed once at the start, then left alone
+    Fixed,
+    /// re-aimed every `minutes`
+    Every { minutes: u32 },
+    /// aimed at the start and re-aimed at each of `times`
+    At { times: Vec<ClockTime> },
+}
+
+/// a day of sun on one oven, from `start` to `end` in steps of `step_minutes`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct DaySimulation {
+    pub location: Location,
+    pub start: DateTime,
+    // same day as `start`
+    pub end: ClockTime,
+    pub step_minutes: u32,
+    pub aiming: Aiming,
+    // hot enough to cook, 204°C (400°F) for our test
+    pub cooking_threshold: Temperature,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct DaySample {
+    pub time: DateTime,
+    pub sun: SunPosition,
+    // sunlight landing on the window
+    pub irradiance: Irradiance,
+    pub tio: Temperature,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct DayReport {
+    pub samples: Vec<DaySample>,
+    // `None` if there were no samples
+    pub peak: Option<DaySample>,
+    pub hours_above_threshold: f64,
+}
+
+impl ClockTime {
+    pub fn minutes(&self) -> u32 {
+        self.hour * 60 + self.minute
+    }
+}
+
+impl DateTime {
+    /// the same day at `minutes` after local midnight
+    pub fn with_minutes(&self, minutes: u32) -> Self {
+        Self {
+            hour: minutes / 60,
+            minute: minutes % 60,
+            ..*self
+        }
+    }
+}
+
+impl DaySimulation {
+    pub fn from_json(s: &str) -> Result<Self, LoadError> {
+        let sim: Self = files::from_json(s)?;
+        sim.validate()?;
+
+        Ok(sim)
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, LoadError> {
+        let sim: Self = files::from_toml(s)?;
+        sim.validate()?;
+
+        Ok(sim)
+    }
+
+    /// picks the format from the file extension, anything that isn't `.toml` is read as JSON
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let sim: Self = files::load(path)?;
+        sim.validate()?;
+
+        Ok(sim)
+    }
+
+    pub fn validate(&self) -> Result<(), LoadError> {
+        positive("day", "step_minutes", self.step_minutes as f64)?;
+
+        if !self.start.is_valid() {
+            return Err(LoadError::Invalid(format!(
+                "day: start ({}) isn't a real date and time",
+                self.start
+            )));
+        }
+        if self.end.minutes() < self.start.minutes() || self.end.hour > 23 || self.end.minute > 59 {
+            return Err(LoadError::Invalid(format!(
+                "day: end ({}) has to be later the same day as start ({})",
+                self.end, self.start
+            )));
+        }
+
+        if let Aiming::Every { minutes } = self.aiming {
+            positive("day", "aiming minutes", minutes as f64)?;
+        }
+
+        Ok(())
+    }
+
+    /// local times of every step
+    pub fn times(&self) -> impl Iterator<Item = DateTime> + '_ {
+        (self.start.minutes()..=self.end.minutes())
+            .step_by(self.step_minutes.max(1) as usize)
+            .map(|m| self.start.with_minutes(m))
+    }
+
+    /// whether the oven is re-aimed at the step at `minutes`, `previous` is the step before
+    fn aims_at(&self, previous: Option<u32>, minutes: u32) -> bool {
+        let Some(previous) = previous else {
+            return true;
+        };
+
+        match &self.aiming {
+            Aiming::Tracking => true,
+            Aiming::Fixed => false,
+            Aiming::Every { minutes: every } => {
+                let start = self.start.minutes();
+                (previous - start) / every != (minutes - start) / every
+            }
+            Aiming::At { times } => times
+                .iter()
+                .any(|t| previous < t.minutes() && t.minutes() <= minutes),
+        }
+    }
+}
+
+impl Design {
+    /// steps through `sim`, moving the sun (and re-aiming the oven when `sim.aiming` says to) and
+    /// solving for the steady state temperature at every step. everything else comes from `env`.
+    ///
+    /// a fixed solar power density would make every step look the same, so if `env` doesn't have
+    /// a clear-sky model the default one is used
+    pub fn simulate_day(&self, sim: &DaySimulation, env: &Environment) -> DayReport {
+        let env = Environment {
+            clear_sky: env.clear_sky.or(Some(ClearSky::default())),
+            ..*env
+        };
+
+        let mut samples = Vec::new();
+        let mut aim = env;
+        let mut previous = None;
+
+        for time in sim.times() {
+            let sun = sim.location.sun_position(&time);
+            let minutes = time.minutes();
+
+            let mut step = env.with_sun(sun);
+            if sim.aims_at(previous, minutes) {
+                aim = step.aimed_at_sun();
+            }
+            step.oven_angle = aim.oven_angle;
+            step.oven_azimuth = aim.oven_azimuth;
+
+            let (irradiance, tio) = if sun.elevation.value() > 0. {
+                (step.window_irradiance(), self.predicted_tio(&step))
+            } else {
+                (Irradiance::default(), step.ambient)
+            };
+
+            samples.push(DaySample {
+                time,
+                sun,
+                irradiance,
+                tio,
+            });
+            previous = Some(minutes);
+        }
+
+        let peak = samples
+            .iter()
+            .copied()
+            .max_by(|a, b| a.tio.as_celsius().total_cmp(&b.tio.as_celsius()));
+
+        let hours_above_threshold = hours_above(&samples, sim.cooking_threshold);
+
+        DayReport {
+            samples,
+            peak,
+            hours_above_threshold,
+        }
+    }
+}
+
+/// how long the oven spent at or above `threshold`, treating the temperature as a straight line
+/// between samples
+fn hours_above(samples: &[DaySample], threshold: Temperature) -> f64 {
+    samples
+        .windows(2)
+        .map(|pair| {
+            let hours = (pair[1].time.minutes() - pair[0].time.minutes()) as f64 / 60.;
+            let (a, b) = (
+                (pair[0].tio - threshold).value(),
+                (pair[1].tio - threshold).value(),
+            );
+
+            match (a >= 0., b >= 0.) {
+                (true, true) => hours,
+                (false, false) => 0.,
+                // the part of the step after it crossed
+                (true, false) => hours * a / (a - b),
+                (false, true) => hours * b / (b - a),
+            }
+        })
+        .sum()
+}
+
+/// "HH:MM"
+impl FromStr for ClockTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || format!("expected a time like \"13:30\", got \"{s}\"");
+
+        let (hour, minute) = s.split_once(':').ok_or_else(bad)?;
+        let hour = hour.parse().map_err(|_| bad())?;
+        let minute = minute.parse().map_err(|_| bad())?;
+
+        if hour > 23 || minute > 59 {
+            return Err(bad());
+        }
+
+        Ok(Self { hour, minute })
+    }
+}
+
+impl Display for ClockTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Angle;
+
+    fn sample(time: DateTime, tio: f64) -> DaySample {
+        DaySample {
+            time,
+            sun: SunPosition {
+                elevation: Angle::radians(0.5),
+                azimuth: Angle::radians(0.),
+            },
+            irradiance: Irradiance::default(),
+            tio: Temperature::celsius(tio),
+        }
+    }
+
+    fn sim() -> DaySimulation {
+        DaySimulation::from_toml(include_str!("../day.toml")).unwrap()
+    }
+
+    #[test]
+    fn hot_all_day() {
+        let sim = sim();
+        let samples: Vec<_> = sim.times().map(|time| sample(time, 250.)).collect();
+
+        // 8:00 to 17:00
+        assert_eq!(hours_above(&samples, sim.cooking_threshold), 9.);
+    }
+
+    #[test]
+    fn crossing_part_way_through_a_step() {
+        let start = sim().start;
+        let samples = [
+            sample(start.with_minutes(600), 200.),
+            sample(start.with_minutes(630), 208.),
+            sample(start.with_minutes(660), 206.),
+            sample(start.with_minutes(690), 196.),
+        ];
+
+        // half of the first step, all of the second, a fifth of the third
+        let hours = hours_above(&samples, Temperature::celsius(204.));
+        assert!((hours - (0.25 + 0.5 + 0.1)).abs() < 1e-9, "{hours}");
+    }
+
+    #[test]
+    fn start_has_to_be_a_real_date() {
+        let mut february = sim();
+        february.start.month = 2;
+        february.start.day = 30;
+        assert!(february.validate().is_err());
+
+        let mut midnight = sim();
+        midnight.start.hour = 24;
+        assert!(midnight.validate().is_err());
+    }
+}
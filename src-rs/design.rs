@@ -1,44 +1,106 @@
-use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::Path};
 
-use crate::*;
-use linreg::linear_regression;
+use crate::{
+    files::{self, non_negative, positive, LoadError},
+    *,
+};
 
-#[derive(Debug, Clone)]
+/// °C, where the built-in windows' Uw was measured
+const MODEL_TEMPERATURES: [f64; 6] = [66., 93., 121., 149., 177., 204.];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
 pub struct Design {
-    pub absorber: Absorber,
-    pub l_and_w: f64,
-    pub h: f64,
-    pub inner_body: BodyMaterial,
-    pub insulator: Insulator,
-    pub insulator_thickness: f64,
-    pub outer_body: BodyMaterial,
-    pub window: WindowMaterial,
-    pub reflectors: ReflectiveMaterial,
+    pub absorber: AbsorberSpec,
+    pub l_and_w: Length,
+    pub h: Length,
+    pub inner_body: BodySpec,
+    pub insulator: InsulatorSpec,
+    pub insulator_thickness: Length,
+    pub outer_body: BodySpec,
+    pub window: WindowSpec,
+    pub reflectors: ReflectiveSpec,
     pub reflector_count: u8,
     pub reflector_ml: f64,
     pub reflector_type: ReflectorType,
+    // what the chamber is supposed to hold, l and w are worked out from it. files can leave it
+    // out, it's then whatever l, w and h hold
+    #[serde(default)]
+    pub volume_target: Volume,
 }
 
 impl Design {
+    pub fn from_json(s: &str) -> Result<Self, LoadError> {
+        files::from_json::<Self>(s)?.loaded()
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, LoadError> {
+        files::from_toml::<Self>(s)?.loaded()
+    }
+
+    /// picks the format from the file extension, anything that isn't `.toml` is read as JSON.
+    /// lengths are in m, the same as `--export` writes them
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        files::load::<Self>(path)?.loaded()
+    }
+
+    fn loaded(mut self) -> Result<Self, LoadError> {
+        if self.volume_target == Volume::default() {
+            self.volume_target = self.chamber_volume();
+        }
+        self.window.fit_uw_line();
+        self.validate()?;
+
+        Ok(self)
+    }
+
+    pub fn validate(&self) -> Result<(), LoadError> {
+        positive("design", "l_and_w", self.l_and_w.value())?;
+        positive("design", "h", self.h.value())?;
+        non_negative(
+            "design",
+            "insulator_thickness",
+            self.insulator_thickness.value(),
+        )?;
+        positive("design", "reflector_count", self.reflector_count as f64)?;
+        positive("design", "reflector_ml", self.reflector_ml)?;
+        positive("design", "volume_target", self.volume_target.value())?;
+
+        // the materials get the same checks as a catalog's
+        let bodies = if self.inner_body == self.outer_body {
+            vec![self.inner_body.clone()]
+        } else {
+            vec![self.inner_body.clone(), self.outer_body.clone()]
+        };
+        Catalog {
+            absorbers: vec![self.absorber.clone()],
+            windows: vec![self.window.clone()],
+            reflective_materials: vec![self.reflectors.clone()],
+            insulators: vec![self.insulator.clone()],
+            body_materials: bodies,
+        }
+        .validate()
+    }
+
     pub fn ok(&self) -> bool {
-        self.l_and_w > 0.
-            && self.h > 0.
-            && self.insulator_thickness > 0.
+        self.l_and_w > Length::default()
+            && self.h > Length::default()
+            && self.insulator_thickness > Length::default()
             && self.reflector_count > 0
             && self.reflector_ml > 0.
             && (self.reflector_ml - 3.).abs() < 0.001
-            && (self.chamber_volume() - 0.001).abs() < 1e-8
+            && (self.chamber_volume() - self.volume_target).abs() < Volume::cubic_meters(1e-8)
     }
 
-    fn chamber_volume(&self) -> f64 {
+    pub fn chamber_volume(&self) -> Volume {
         self.l_and_w * self.l_and_w * self.h
     }
-    fn usb(&self) -> f64 {
-        // (x1/k1 + x2/k2 + x3/k3)^-1
 
-        // intentionally using inches
+    fn usb(&self) -> UValue {
+        // (x1/k1 + x2/k2 + x3/k3)^-1
         let iw = self.inner_body.thickness() / self.inner_body.conductivity();
-        let c = self.insulator_thickness_in() / self.insulator.conductivity();
+        let c = self.insulator_thickness / self.insulator.conductivity();
         let ow = self.outer_body.thickness() / self.outer_body.conductivity();
 
         let sum = iw + c + ow;
@@ -46,104 +108,244 @@ impl Design {
         sum.recip()
     }
 
-    fn transmitivity(&self) -> f64 {
-        self.window.transmitivity()
+    fn absorptivity(&self) -> f64 {
+        self.absorber.absorptivity
     }
 
-    fn absorptivity(&self) -> f64 {
-        self.absorber.absoptivity()
+    /// sunlight that makes it through the window, per m^2 of window. the beam goes through at
+    /// the sun's incidence angle, the diffuse sky at the equivalent angle for the window's tilt
+    pub fn transmitted_irradiance(&self, env: &Environment) -> Irradiance {
+        env.beam_irradiance() * self.window.transmittance(env.incidence_angle())
+            + env.diffuse_irradiance() * self.window.transmittance(env.diffuse_angle())
     }
 
-    fn tio_at_uw(&self, uw: f64) -> f64 {
-        let num = SOLAR_POWER_DENSITY
-            * self.aw()
-            * self.transmitivity()
-            * self.absorptivity()
-            * (OVEN_ANGLE + SUN_ANGLE).sin()
-            * self.gain();
+    /// sunlight absorbed inside the oven
+    pub fn solar_gain(&self, env: &Environment) -> Power {
+        self.transmitted_irradiance(env) * self.aw() * self.absorptivity() * self.gain()
+    }
 
-        let denom = self.usb() * self.asb() + uw * self.aw();
+    /// effective emissivity between the absorber and the outside, through every layer of the
+    /// window. each layer is a radiation shield, so it's the usual parallel plates formula with
+    /// `layers - 1` shields in between
+    pub fn radiative_emittance(&self) -> f64 {
+        let absorber = self.absorber.emissivity;
+        let window = self.window.emissivity;
+        let shields = (self.window.layers - 1) as f64;
 
-        AMBIENT + num / denom
+        (1. / absorber + 1. / window - 1. + shields * (2. / window - 1.)).recip()
     }
 
-    pub(crate) fn aw(&self) -> f64 {
-        self.l_and_w * self.l_and_w
+    /// the absorber radiating out through the window when it's at `tio`.
+    ///
+    /// σε(T^4 - Ta^4) = σε(T^2 + Ta^2)(T + Ta) * (T - Ta), so this is exact at `tio` but depends on
+    /// it, which is why the solvers have to handle it
+    pub fn radiative_conductance(&self, tio: Temperature, env: &Environment) -> ThermalConductance {
+        let t = tio.as_kelvin();
+        let ta = env.ambient.as_kelvin();
+        let h = STEFAN_BOLTZMANN * self.radiative_emittance() * (t * t + ta * ta) * (t + ta);
+
+        UValue::watts_per_square_meter_kelvin(h) * self.aw()
+    }
+
+    /// heat lost per degree above ambient when the oven is at `tio`: conduction through the walls
+    /// and window plus radiation out of the window. the glazing model's Uw already has the
+    /// radiation in it
+    pub fn loss_conductance(
+        &self,
+        uw: UValue,
+        tio: Temperature,
+        env: &Environment,
+    ) -> ThermalConductance {
+        let radiation = match self.window.glazing {
+            Some(_) => ThermalConductance::default(),
+            None => self.radiative_conductance(tio, env),
+        };
+
+        self.usb() * self.asb() + uw * self.aw() + radiation
     }
 
-    pub(crate) fn asb(&self) -> f64 {
-        self.aw() + 4. * self.h * self.l_and_w
+    /// the window's Uw when the oven is at `tio`, from its measured table or its glazing model
+    pub fn uw_at(&self, tio: Temperature, env: &Environment) -> UValue {
+        match &self.window.glazing {
+            Some(glazing) => glazing.u_value(
+                &self.window,
+                &GlazingConditions {
+                    inside: tio,
+                    outside: env.ambient,
+                    inside_emissivity: self.absorber.emissivity,
+                    wind_speed: env.wind_speed,
+                    tilt: env.oven_angle,
+                    width: self.l_and_w,
+                },
+            ),
+            None => self.window.uw_at(tio),
+        }
     }
 
-    pub fn tio_line(&self) -> Result<LNTrendline, ()> {
-        // x
-        let uws: Vec<_> = self.window.uws().into_iter().map(|n| n.0.ln()).collect();
+    /// the window's (Uw, temperature) points. windows without a measured table get the glazing
+    /// model at the same temperatures the built-in tables were measured at
+    pub fn window_uws(&self, env: &Environment) -> Vec<(UValue, Temperature)> {
+        if self.window.glazing.is_none() {
+            return self.window.uws();
+        }
+
+        MODEL_TEMPERATURES
+            .iter()
+            .map(|&t| {
+                let t = Temperature::celsius(t);
+                (self.uw_at(t, env), t)
+            })
+            .collect()
+    }
 
-        // y
-        let tios: Vec<_> = self
-            .window
-            .uws()
+    /// y = temperature, x = uw, through `window_uws`
+    pub fn window_line(&self, env: &Environment) -> Result<LNTrendline, linreg::Error> {
+        if self.window.glazing.is_none() {
+            return self.window.uw_line();
+        }
+
+        let points: Vec<_> = self
+            .window_uws(env)
             .into_iter()
-            .map(|n| n.0)
-            .map(|n| self.tio_at_uw(n))
+            .map(|(uw, t)| (uw.value(), t.as_celsius()))
             .collect();
 
-        let (a, b) = linear_regression(&uws, &tios).map_err(|_| ())?;
+        LNTrendline::fit(&points)
+    }
 
-        Ok(LNTrendline {
-            coefficient: a,
-            intercept: b,
-        })
+    /// the temperature the oven would settle at if the window's Uw was `uw` and the radiative
+    /// loss was the one at `radiating_at`
+    pub(crate) fn tio_at_uw(
+        &self,
+        uw: UValue,
+        radiating_at: Temperature,
+        env: &Environment,
+    ) -> Temperature {
+        env.ambient + self.solar_gain(env) / self.loss_conductance(uw, radiating_at, env)
     }
 
-    pub fn predicted_tio(&self) -> f64 {
-        let tio_line = self.tio_line();
-        let window_line = self.window.uw_line();
+    pub(crate) fn aw(&self) -> Area {
+        self.l_and_w * self.l_and_w
+    }
 
-        tio_line
-            .map(|l| l.y_intercept(&window_line))
-            .unwrap_or(AMBIENT)
+    pub(crate) fn asb(&self) -> Area {
+        self.aw() + 4. * (self.h * self.l_and_w)
     }
 
-    /// lower is better
-    pub fn score(&self) -> f64 {
-        let tio = self.predicted_tio();
-        let cost = self.total_cost();
+    /// outside length and width of the box, not counting the reflectors
+    pub fn outer_width(&self) -> Length {
+        self.l_and_w
+            + self.inner_body.thickness() * 2.
+            + self.insulator_thickness * 2.
+            + self.outer_body.thickness() * 2.
+    }
+
+    /// outside height of the box, the window is on top so only the bottom has walls
+    pub fn outer_height(&self) -> Length {
+        self.h
+            + self.inner_body.thickness()
+            + self.insulator_thickness
+            + self.outer_body.thickness()
+    }
 
-        // we want to get to at least 400F (204C) to cook the food correctly,
-        // but we set the goal at 250C because it doesn't make a big difference
-        // on cost, and we don't want to leave performance on the table for a tiny
-        // cost improvement
-        (GOAL_TIO - tio).abs() + cost * 2.
+    pub fn tio_line(&self, env: &Environment) -> Result<LNTrendline, linreg::Error> {
+        // (uw, tio), the radiative loss is linearized at each tabulated temperature
+        let points: Vec<_> = self
+            .window_uws(env)
+            .into_iter()
+            .map(|(uw, t)| (uw.value(), self.tio_at_uw(uw, t, env).as_celsius()))
+            .collect();
+
+        LNTrendline::fit(&points)
+    }
+
+    pub fn trendline_tio(&self, env: &Environment) -> Temperature {
+        self.trendline_intersection(env).unwrap_or(env.ambient)
+    }
+
+    /// `tio_line` and `window_line` from the same window points (the glazing model isn't free)
+    /// and where they cross
+    pub(crate) fn trendline_intersection(
+        &self,
+        env: &Environment,
+    ) -> Result<Temperature, linreg::Error> {
+        let uws = self.window_uws(env);
+
+        let tio: Vec<_> = uws
+            .iter()
+            .map(|&(uw, t)| (uw.value(), self.tio_at_uw(uw, t, env).as_celsius()))
+            .collect();
+
+        let tio_line = LNTrendline::fit(&tio)?;
+        let window_line = match self.window.glazing {
+            // the measured table, which doesn't change with the design
+            None => self.window.uw_line()?,
+            Some(_) => {
+                let window: Vec<_> = uws
+                    .iter()
+                    .map(|(uw, t)| (uw.value(), t.as_celsius()))
+                    .collect();
+                LNTrendline::fit(&window)?
+            }
+        };
 
-        // ((tio - AMBIENT) / cost).recip()
+        Ok(Temperature::celsius(tio_line.y_intercept(&window_line)))
+    }
 
-        // tio.recip()
+    /// interior temperature, using `env.tio_method`
+    pub fn predicted_tio(&self, env: &Environment) -> Temperature {
+        match env.tio_method {
+            // skips the residual `solve_tio` works out, this runs for every score
+            TioMethod::Trendline => self.trendline_tio(env),
+            method => self.solve_tio(method, env).tio,
+        }
     }
 
-    pub fn performance_index(&self) -> f64 {
-        let tio = self.predicted_tio();
+    /// lower is better, using the default objective. see `Objective` for the others
+    pub fn score(&self, env: &Environment) -> f64 {
+        GoalDistance::default().score(self, env)
+    }
+
+    pub fn performance_index(&self, env: &Environment) -> f64 {
+        let tio = self.predicted_tio(env);
         let cost = self.total_cost();
 
-        (tio - AMBIENT) / cost
+        (tio - env.ambient).value() / cost
     }
 
-    pub fn insulator_thickness_in(&self) -> f64 {
-        self.insulator_thickness / 2.54
+    /// everything `Display` shows, plus the predicted temperature in `env`
+    pub fn report<'a>(&'a self, env: &'a Environment) -> DesignReport<'a> {
+        DesignReport { design: self, env }
+    }
+}
+
+pub struct DesignReport<'a> {
+    design: &'a Design,
+    env: &'a Environment,
+}
+
+impl Display for DesignReport<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.design)?;
+        writeln!(
+            f,
+            "Temp: {:.4}°C",
+            self.design.predicted_tio(self.env).as_celsius()
+        )
     }
 }
 
 impl Display for Design {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Absorber: {}", self.absorber)?;
-        writeln!(f, "L and W: {:.4} cm", self.l_and_w * 100.)?;
-        writeln!(f, "H: {:.4} cm", self.h * 100.)?;
+        writeln!(f, "L and W: {:.4} cm", self.l_and_w.cm())?;
+        writeln!(f, "H: {:.4} cm", self.h.cm())?;
         writeln!(f, "Inner Body: {:.4}", self.inner_body)?;
         writeln!(f, "Insulator: {}", self.insulator)?;
         writeln!(
             f,
             "Insulator Thickness: {:.4} cm",
-            self.insulator_thickness * 100.
+            self.insulator_thickness.cm()
         )?;
         writeln!(f, "Outer Body: {:.4}", self.outer_body)?;
         writeln!(f, "Window: {}", self.window)?;
@@ -151,26 +353,82 @@ impl Display for Design {
         writeln!(f, "Reflector Count: {}", self.reflector_count)?;
         writeln!(f, "Reflector ML: {:.4}", self.reflector_ml)?;
         writeln!(f, "Reflector Type: {:?}", self.reflector_type)?;
-        writeln!(f, "Cost: ${:.4}", self.total_cost())?;
-        writeln!(f, "Temp: {:.4}°C", self.predicted_tio())
+        writeln!(f, "Cost: ${:.4}", self.total_cost())
     }
 }
 
+/// a `CHAMBER_VOLUME` design
 impl From<(&Oven, &[f64])> for Design {
     fn from((oven, x): (&Oven, &[f64])) -> Self {
+        (oven, x, CHAMBER_VOLUME).into()
+    }
+}
+
+/// x is [h, insulator_thickness, reflector_ml], the same as the optimizer's
+impl From<(&Oven, &[f64], Volume)> for Design {
+    fn from((oven, x, volume): (&Oven, &[f64], Volume)) -> Self {
+        let h = Length::meters(x[0]);
+
         Design {
-            absorber: oven.abs,
-            window: oven.window,
-            l_and_w: (0.001 / x[0]).sqrt(),
-            h: x[0],
-            outer_body: oven.outer_body,
-            inner_body: oven.inner_body,
-            insulator: oven.insulator,
-            insulator_thickness: x[1],
+            absorber: oven.abs.clone(),
+            window: oven.window.clone(),
+            l_and_w: (volume / h).sqrt(),
+            h,
+            outer_body: oven.outer_body.clone(),
+            inner_body: oven.inner_body.clone(),
+            insulator: oven.insulator.clone(),
+            insulator_thickness: Length::meters(x[1]),
             reflector_count: oven.reflector_number,
             reflector_type: oven.reflector_type,
             reflector_ml: x[2],
-            reflectors: oven.reflective_material,
+            reflectors: oven.reflective_material.clone(),
+            volume_target: volume,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Design {
+        Design::from_toml(include_str!("../design.toml")).unwrap()
+    }
+
+    /// parallel plates: 1/ε = 1/ε1 + 1/ε2 - 1, and every shield in between adds 2/ε - 1
+    #[test]
+    fn window_layers_shield_radiation() {
+        let mut design = example();
+
+        design.absorber.emissivity = 1.;
+        design.window.emissivity = 1.;
+        design.window.layers = 1;
+        assert!((design.radiative_emittance() - 1.).abs() < 1e-12);
+
+        design.absorber.emissivity = 0.9;
+        design.window.emissivity = 0.5;
+        assert!((design.radiative_emittance() - 1. / (1. / 0.9 + 1.)).abs() < 1e-12);
+
+        design.window.layers = 2;
+        assert!((design.radiative_emittance() - 1. / (1. / 0.9 + 1. + 3.)).abs() < 1e-12);
+    }
+
+    /// the linearized conductance times the temperature difference is the full σεA(T^4 - Ta^4)
+    #[test]
+    fn radiation_is_stefan_boltzmann() {
+        let design = example();
+        let env = Environment::default();
+        let tio = Temperature::celsius(200.);
+
+        let linearized = (design.radiative_conductance(tio, &env) * (tio - env.ambient)).value();
+        let full = STEFAN_BOLTZMANN
+            * design.radiative_emittance()
+            * design.aw().value()
+            * (tio.as_kelvin().powi(4) - env.ambient.as_kelvin().powi(4));
+
+        assert!(
+            (linearized - full).abs() < 1e-9 * full,
+            "{linearized} vs {full}"
+        );
+    }
+}
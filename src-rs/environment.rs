@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    files::{self, non_negative, LoadError},
+    Angle, ClearSky, DateTime, Irradiance, Location, SunPosition, Temperature, TioMethod,
+};
+
+// the conditions a design is evaluated in.
+//
+// the default is the class test: full sun, a 21°C day, the sun 50° above the horizon and the oven
+// tilted to face it. any field missing from a JSON/TOML file falls back to the default
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[wasm_bindgen]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct Environment {
+    // used as-is when there's no `clear_sky`
+    #[wasm_bindgen(skip)]
+    pub solar_power_density: Irradiance,
+    // when set, sunlight comes from the clear-sky model at the current sun angle instead
+    #[wasm_bindgen(skip)]
+    pub clear_sky: Option<ClearSky>,
+    #[wasm_bindgen(skip)]
+    pub ambient: Temperature,
+    // m/s, cools the outside of windows that use the glazing model
+    #[wasm_bindgen(skip)]
+    pub wind_speed: f64,
+    // elevation of the sun above the horizon
+    #[wasm_bindgen(skip)]
+    pub sun_angle: Angle,
+    // clockwise from north
+    #[wasm_bindgen(skip)]
+    pub sun_azimuth: Angle,
+    // tilt of the window from horizontal, pi/2 - sun_angle points it straight at the sun
+    #[wasm_bindgen(skip)]
+    pub oven_angle: Angle,
+    // the compass direction the window faces, clockwise from north
+    #[wasm_bindgen(skip)]
+    pub oven_azimuth: Angle,
+    // what `Design::score` aims for, 400F (204C) is enough to cook the food correctly,
+    // but we set the goal at 250C because it doesn't make a big difference
+    // on cost, and we don't want to leave performance on the table for a tiny
+    // cost improvement
+    #[wasm_bindgen(skip)]
+    pub goal_tio: Temperature,
+    #[wasm_bindgen(skip)]
+    pub tio_method: TioMethod,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        let sun_angle = Angle::degrees(50.);
+
+        Self {
+            solar_power_density: Irradiance::watts_per_square_meter(1000.),
+            clear_sky: None,
+            ambient: Temperature::celsius(21.),
+            wind_speed: 1.,
+            sun_angle,
+            sun_azimuth: Angle::degrees(180.),
+            oven_angle: Angle::degrees(90.) - sun_angle,
+            oven_azimuth: Angle::degrees(180.),
+            goal_tio: Temperature::celsius(250.),
+            tio_method: TioMethod::default(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Environment {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the sun's position at a place and local time, with the oven re-aimed at it
+    #[allow(clippy::too_many_arguments)]
+    pub fn at_location(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        utc_offset: f64,
+    ) -> Environment {
+        let location = Location {
+            latitude,
+            longitude,
+        };
+        let time = DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            utc_offset,
+        };
+
+        self.at(&location, &time).aimed_at_sun()
+    }
+}
+
+impl Environment {
+    pub fn from_json(s: &str) -> Result<Self, LoadError> {
+        let environment: Self = files::from_json(s)?;
+        environment.validate()?;
+
+        Ok(environment)
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, LoadError> {
+        let environment: Self = files::from_toml(s)?;
+        environment.validate()?;
+
+        Ok(environment)
+    }
+
+    /// picks the format from the file extension, anything that isn't `.toml` is read as JSON
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let environment: Self = files::load(path)?;
+        environment.validate()?;
+
+        Ok(environment)
+    }
+
+    pub fn validate(&self) -> Result<(), LoadError> {
+        non_negative(
+            "environment",
+            "solar_power_density",
+            self.solar_power_density.value(),
+        )?;
+
+        non_negative("environment", "wind_speed", self.wind_speed)?;
+
+        let sun = self.sun_angle.to_degrees();
+        if !(0. ..=90.).contains(&sun) {
+            return Err(LoadError::Invalid(format!(
+                "environment: sun_angle must be between 0 and 90 degrees, got {sun}"
+            )));
+        }
+
+        let oven = self.oven_angle.to_degrees();
+        if !(0. ..=90.).contains(&oven) {
+            return Err(LoadError::Invalid(format!(
+                "environment: oven_angle must be between 0 and 90 degrees, got {oven}"
+            )));
+        }
+
+        if let Some(sky) = self.clear_sky {
+            if !(1. ..=10.).contains(&sky.linke_turbidity) {
+                return Err(LoadError::Invalid(format!(
+                    "environment: linke_turbidity must be between 1 and 10, got {}",
+                    sky.linke_turbidity
+                )));
+            }
+            if !(-500. ..=9000.).contains(&sky.altitude.value()) {
+                return Err(LoadError::Invalid(format!(
+                    "environment: altitude must be between -500 and 9000 m, got {}",
+                    sky.altitude.value()
+                )));
+            }
+        }
+
+        if self.ambient.is_nan() || self.goal_tio.is_nan() {
+            return Err(LoadError::Invalid(
+                "environment: temperatures can't be NaN".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// the fraction of the sun's power that hits the window, 1 when it's pointed straight at it.
+    ///
+    /// this is the cosine of the angle between the sun and the window's normal, which is
+    /// `sin(oven_angle + sun_angle)` when the oven faces the same direction as the sun
+    pub fn incidence_factor(&self) -> f64 {
+        let azimuth = (self.sun_azimuth - self.oven_azimuth).cos();
+
+        (self.sun_angle.sin() * self.oven_angle.cos()
+            + self.sun_angle.cos() * self.oven_angle.sin() * azimuth)
+            .max(0.)
+    }
+
+    /// angle between the sun and the window's normal, 90° when the sun is behind the window
+    pub fn incidence_angle(&self) -> Angle {
+        Angle::radians(self.incidence_factor().acos())
+    }
+
+    /// sunlight landing on the window, per m^2 of window.
+    ///
+    /// with a clear sky this is the direct beam hitting the window at an angle plus the part of
+    /// the (isotropic) diffuse sky the tilted window can see
+    pub fn window_irradiance(&self) -> Irradiance {
+        self.beam_irradiance() + self.diffuse_irradiance()
+    }
+
+    /// the part of `window_irradiance` coming straight from the sun, at `incidence_angle`.
+    /// a fixed `solar_power_density` is all beam
+    pub fn beam_irradiance(&self) -> Irradiance {
+        match self.clear_sky {
+            None => self.solar_power_density * self.incidence_factor(),
+            Some(sky) => sky.irradiance(self.sun_angle).direct_normal * self.incidence_factor(),
+        }
+    }
+
+    /// the part of `window_irradiance` scattered from the rest of the sky, see `diffuse_angle`
+    pub fn diffuse_irradiance(&self) -> Irradiance {
+        match self.clear_sky {
+            None => Irradiance::default(),
+            Some(sky) => {
+                sky.irradiance(self.sun_angle).diffuse_horizontal
+                    * ((1. + self.oven_angle.cos()) / 2.)
+            }
+        }
+    }
+
+    /// the incidence angle that lets the window pass as much diffuse sky light as it really does,
+    /// from Brandemuehl and Beckman's fit for a window tilted at `oven_angle`
+    pub fn diffuse_angle(&self) -> Angle {
+        let tilt = self.oven_angle.to_degrees();
+
+        Angle::degrees(59.7 - 0.1388 * tilt + 0.001497 * tilt * tilt)
+    }
+
+    /// the same environment with the sun moved to `position`, the oven stays where it was.
+    /// a sun below the horizon is clamped to 0°
+    pub fn with_sun(&self, position: SunPosition) -> Self {
+        Self {
+            sun_angle: Angle::radians(position.elevation.value().max(0.)),
+            sun_azimuth: position.azimuth,
+            ..*self
+        }
+    }
+
+    /// the sun's position at `location` and `time`, see `with_sun`
+    pub fn at(&self, location: &Location, time: &DateTime) -> Self {
+        self.with_sun(location.sun_position(time))
+    }
+
+    /// the oven turned and tilted so the window faces the sun
+    pub fn aimed_at_sun(&self) -> Self {
+        Self {
+            oven_angle: Angle::degrees(90.) - self.sun_angle,
+            oven_azimuth: self.sun_azimuth,
+            ..*self
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn environment_from_json(environment: &str) -> Option<Environment> {
+    Environment::from_json(environment).ok()
+}
@@ -0,0 +1,74 @@
+//! how one design does, without optimizing anything. for checking an oven that's already been
+//! built (or a design from `--export`) against the same models the optimizer uses
+
+use serde::Serialize;
+
+use crate::{
+    Constraints, CostBreakdown, Design, Environment, HeatUp, Objective, TioMethod, Violation,
+};
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct Evaluation {
+    pub design: Design,
+    // lower is better, from whichever objective it was evaluated with
+    pub score: f64,
+    // $
+    pub cost: f64,
+    pub cost_breakdown: CostBreakdown,
+    // kg
+    pub weight: f64,
+    // °C, using `env.tio_method`
+    pub tio: f64,
+    pub trendline_tio: f64,
+    pub energy_balance_tio: f64,
+    // whether the energy balance solver found its temperature
+    pub energy_balance_converged: bool,
+    pub performance_index: f64,
+    // W, sunlight absorbed inside the oven
+    pub solar_gain: f64,
+    pub reflector_gain: f64,
+    // L
+    pub chamber_volume: f64,
+    // cm
+    pub outer_width: f64,
+    pub outer_height: f64,
+    // s to get to the heat up target, none if it never does
+    pub time_to_target: Option<f64>,
+    // limits the design breaks, empty when it's inside the constraints
+    pub violations: Vec<Violation>,
+}
+
+impl Design {
+    /// cost, temperatures (with both methods), heat up time and everything else the optimizer
+    /// would've looked at
+    pub fn evaluate(
+        &self,
+        env: &Environment,
+        objective: &dyn Objective,
+        constraints: &Constraints,
+        heat_up: &HeatUp,
+    ) -> Evaluation {
+        let energy_balance = self.solve_tio(TioMethod::EnergyBalance, env);
+
+        Evaluation {
+            design: self.clone(),
+            score: objective.score(self, env),
+            cost: self.total_cost(),
+            cost_breakdown: self.cost_breakdown(),
+            weight: self.total_weight(),
+            tio: self.predicted_tio(env).as_celsius(),
+            trendline_tio: self.trendline_tio(env).as_celsius(),
+            energy_balance_tio: energy_balance.tio.as_celsius(),
+            energy_balance_converged: energy_balance.converged,
+            performance_index: self.performance_index(env),
+            solar_gain: self.solar_gain(env).value(),
+            reflector_gain: self.gain(),
+            chamber_volume: self.chamber_volume().as_liters(),
+            outer_width: self.outer_width().cm(),
+            outer_height: self.outer_height().cm(),
+            time_to_target: self.heat_up(heat_up, env).time_to_target,
+            violations: constraints.violations(self, env),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use serde::de::DeserializeOwned;
+use std::{fmt::Display, path::Path};
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Parse(String),
+    Invalid(String),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "couldn't read file: {e}"),
+            LoadError::Parse(e) => write!(f, "couldn't parse file: {e}"),
+            LoadError::Invalid(e) => write!(f, "invalid file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+pub(crate) fn from_json<T: DeserializeOwned>(s: &str) -> Result<T, LoadError> {
+    serde_json::from_str(s).map_err(|e| LoadError::Parse(e.to_string()))
+}
+
+pub(crate) fn from_toml<T: DeserializeOwned>(s: &str) -> Result<T, LoadError> {
+    toml::from_str(s).map_err(|e| LoadError::Parse(e.to_string()))
+}
+
+/// picks the format from the file extension, anything that isn't `.toml` is read as JSON
+pub(crate) fn load<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, LoadError> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path).map_err(LoadError::Io)?;
+
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => from_toml(&contents),
+        _ => from_json(&contents),
+    }
+}
+
+pub(crate) fn non_negative(name: &str, field: &str, value: f64) -> Result<(), LoadError> {
+    if value.is_finite() && value >= 0. {
+        Ok(())
+    } else {
+        Err(LoadError::Invalid(format!(
+            "{name}: {field} must be >= 0, got {value}"
+        )))
+    }
+}
+
+pub(crate) fn positive(name: &str, field: &str, value: f64) -> Result<(), LoadError> {
+    if value.is_finite() && value > 0. {
+        Ok(())
+    } else {
+        Err(LoadError::Invalid(format!(
+            "{name}: {field} must be > 0, got {value}"
+        )))
+    }
+}
+
+pub(crate) fn fraction(name: &str, field: &str, value: f64) -> Result<(), LoadError> {
+    if (0. ..=1.).contains(&value) {
+        Ok(())
+    } else {
+        Err(LoadError::Invalid(format!(
+            "{name}: {field} must be between 0 and 1, got {value}"
+        )))
+    }
+}
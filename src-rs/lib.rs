@@ -1,62 +1,66 @@
+mod browser;
+mod choices;
+mod constraints;
 mod cost;
+mod day;
 mod design;
+mod environment;
+mod evaluate;
 pub mod export;
+mod files;
+mod irradiance;
 mod materials;
+mod multistart;
+mod objective;
+mod optimization;
+mod optimizers;
 mod oven;
+mod pareto;
 mod reflectors;
+mod search;
+mod solver;
+mod sun;
+mod transient;
 mod trendline;
+mod units;
+mod weight;
 
-pub use design::Design;
+pub use browser::{optimize_chunk, optimize_oven, oven_count, OptimizedOven, Progress};
+pub use choices::{Category, Choices, Filter};
+pub use constraints::{constraints_from_json, Constraints, Limit, Margin, Violation};
+pub use cost::CostBreakdown;
+pub use day::{Aiming, ClockTime, DayReport, DaySample, DaySimulation};
+pub use design::{Design, DesignReport};
+pub use environment::{environment_from_json, Environment};
+pub use evaluate::Evaluation;
+pub use files::LoadError;
+pub use irradiance::{air_mass, ClearSky, SkyIrradiance};
 pub use materials::*;
-pub use oven::{score, GraphDataResponse, Oven};
+pub use multistart::{MultiStart, MultiStartResult, Sampling};
+pub use objective::{
+    objective_from_json, BuiltinObjective, GoalDistance, MaxTempUnderBudget, MinCostAboveTemp,
+    Objective, ObjectiveChoice, PerformanceIndex,
+};
+pub use optimization::{optimization_spec_from_json, OptimizationSpec};
+pub use optimizers::*;
+pub use oven::{GraphDataResponse, OptimizationResult, Oven};
+pub use pareto::{pareto_front, ParetoPoint, BUDGETS};
 pub use reflectors::ReflectorType;
+pub use search::{GeneticSearch, SearchResult};
+pub use solver::{TioMethod, TioSolution};
+pub use sun::{DateTime, Location, SunPosition};
+pub use transient::{time_to_target, HeatUp, HeatUpReport, HeatUpSample, ThermalMass};
 pub use trendline::LNTrendline;
+pub use units::*;
 
-use itertools::Itertools;
+/// the inside of the oven has to be 1 L for the class competition, see `OptimizationSpec` for
+/// other sizes
+pub const CHAMBER_VOLUME: Volume = Volume::liters(1.);
 
-pub const SOLAR_POWER_DENSITY: f64 = 1000.;
-pub const AMBIENT: f64 = 21.;
-/// 50 degrees in radians
-pub const SUN_ANGLE: f64 = 0.872664626;
-/// pi/2 - sun_angle (in radians)
-pub const OVEN_ANGLE: f64 = 1.5707963268 - SUN_ANGLE;
-/// 400 F in Celsius
-pub const GOAL_TIO: f64 = 250.;
+/// W/(m^2*K^4)
+pub(crate) const STEFAN_BOLTZMANN: f64 = 5.670374419e-8;
 
-const INITIALS: &'static [[f64; 3]] = &[[0.1, 0.1, 3.] /* [0.4, 1.5, 3.] */];
-
-pub fn variants() -> Vec<(Oven, [f64; 3])> {
-    (itertools::iproduct![
-        Absorber::variants(),
-        WindowMaterial::variants(),
-        BodyMaterial::variants(),
-        BodyMaterial::variants(),
-        Insulator::variants(),
-        ReflectorType::variants(),
-        ReflectiveMaterial::variants(),
-        1..=4u8
-    ])
-    .map(
-        |(
-            &abs,
-            &window,
-            &outer_body,
-            &inner_body,
-            &insulator,
-            &reflector_type,
-            &reflective_material,
-            reflector_number,
-        )| Oven {
-            abs,
-            window,
-            outer_body,
-            inner_body,
-            insulator,
-            reflector_type,
-            reflective_material,
-            reflector_number,
-        },
-    )
-    .cartesian_product(INITIALS.iter().cloned())
-    .collect()
+/// every oven made from the built-in catalog, see `Catalog::variants` for custom catalogs
+pub fn variants(spec: &OptimizationSpec) -> Vec<(Oven, [f64; 3])> {
+    Catalog::builtin().variants(spec)
 }
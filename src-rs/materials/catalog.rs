@@ -0,0 +1,372 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt::Display, path::Path};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    files::{self, fraction, non_negative, positive, LoadError},
+    Absorber, Area, BodyMaterial, Conductivity, Insulator, Length, OptimizationSpec, Oven,
+    ReflectiveMaterial, Volume, WindowMaterial, WindowSpec,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[wasm_bindgen(getter_with_clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct AbsorberSpec {
+    pub name: String,
+    pub cost_per_m2: f64,
+    // of sunlight
+    pub absorptivity: f64,
+    // in the thermal infrared, what it radiates at oven temperatures
+    pub emissivity: f64,
+    pub kg_per_m2: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[wasm_bindgen(getter_with_clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct ReflectiveSpec {
+    pub name: String,
+    pub cost_per_m2: f64,
+    pub reflectivity: f64,
+    pub kg_per_m2: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[wasm_bindgen(getter_with_clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct InsulatorSpec {
+    pub name: String,
+    pub cost_per_m3: f64,
+    // W/(m*K)
+    pub conductivity: f64,
+    pub kg_per_m3: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[wasm_bindgen(getter_with_clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct BodySpec {
+    pub name: String,
+    pub cost_per_m2: f64,
+    // W/(m*K)
+    pub conductivity: f64,
+    // inches, because that's how the store lists them
+    pub thickness_in: f64,
+    pub kg_per_m3: f64,
+}
+
+impl AbsorberSpec {
+    pub fn cost(&self, area: Area) -> f64 {
+        self.cost_per_m2 * area.value()
+    }
+
+    /// kg
+    pub fn mass(&self, area: Area) -> f64 {
+        self.kg_per_m2 * area.value()
+    }
+}
+
+impl ReflectiveSpec {
+    pub fn cost(&self, area: Area) -> f64 {
+        self.cost_per_m2 * area.value()
+    }
+
+    /// kg
+    pub fn mass(&self, area: Area) -> f64 {
+        self.kg_per_m2 * area.value()
+    }
+}
+
+impl InsulatorSpec {
+    pub fn cost(&self, volume: Volume) -> f64 {
+        self.cost_per_m3 * volume.value()
+    }
+
+    /// kg
+    pub fn mass(&self, volume: Volume) -> f64 {
+        self.kg_per_m3 * volume.value()
+    }
+
+    pub fn conductivity(&self) -> Conductivity {
+        Conductivity::watts_per_meter_kelvin(self.conductivity)
+    }
+}
+
+impl BodySpec {
+    pub fn cost(&self, area: Area) -> f64 {
+        self.cost_per_m2 * area.value()
+    }
+
+    /// kg of a wall with `area`, `thickness` thick
+    pub fn mass(&self, area: Area) -> f64 {
+        self.kg_per_m3 * (area * self.thickness()).value()
+    }
+
+    pub fn conductivity(&self) -> Conductivity {
+        Conductivity::watts_per_meter_kelvin(self.conductivity)
+    }
+
+    pub fn thickness(&self) -> Length {
+        Length::inches(self.thickness_in)
+    }
+}
+
+macro_rules! display_name {
+    ($($ty:ident),+) => {
+        $(
+            impl Display for $ty {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "{}", self.name)
+                }
+            }
+        )+
+    };
+}
+
+display_name!(AbsorberSpec, ReflectiveSpec, InsulatorSpec, BodySpec);
+
+/// every material the optimizer is allowed to pick from.
+///
+/// `Catalog::builtin()` is generated from the macros in `materials/mod.rs`, but a catalog can
+/// also be loaded from a JSON or TOML file so that prices and new materials don't need a recompile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct Catalog {
+    pub absorbers: Vec<AbsorberSpec>,
+    pub windows: Vec<WindowSpec>,
+    pub reflective_materials: Vec<ReflectiveSpec>,
+    pub insulators: Vec<InsulatorSpec>,
+    pub body_materials: Vec<BodySpec>,
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl Catalog {
+    pub fn builtin() -> Self {
+        Self {
+            absorbers: Absorber::variants().iter().map(|a| a.spec()).collect(),
+            windows: WindowMaterial::variants()
+                .iter()
+                .map(|w| w.spec())
+                .collect(),
+            reflective_materials: ReflectiveMaterial::variants()
+                .iter()
+                .map(|r| r.spec())
+                .collect(),
+            insulators: Insulator::variants().iter().map(|i| i.spec()).collect(),
+            body_materials: BodyMaterial::variants().iter().map(|b| b.spec()).collect(),
+        }
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, LoadError> {
+        files::from_json::<Self>(s)?.loaded()
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, LoadError> {
+        files::from_toml::<Self>(s)?.loaded()
+    }
+
+    /// picks the format from the file extension, anything that isn't `.toml` is read as JSON
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        files::load::<Self>(path)?.loaded()
+    }
+
+    fn loaded(mut self) -> Result<Self, LoadError> {
+        for w in &mut self.windows {
+            w.fit_uw_line();
+        }
+        self.validate()?;
+
+        Ok(self)
+    }
+
+    pub fn validate(&self) -> Result<(), LoadError> {
+        unique_names("absorbers", self.absorbers.iter().map(|a| &a.name))?;
+        unique_names("windows", self.windows.iter().map(|w| &w.name))?;
+        unique_names(
+            "reflective_materials",
+            self.reflective_materials.iter().map(|r| &r.name),
+        )?;
+        unique_names("insulators", self.insulators.iter().map(|i| &i.name))?;
+        unique_names(
+            "body_materials",
+            self.body_materials.iter().map(|b| &b.name),
+        )?;
+
+        for a in &self.absorbers {
+            non_negative(&a.name, "cost_per_m2", a.cost_per_m2)?;
+            fraction(&a.name, "absorptivity", a.absorptivity)?;
+            positive(&a.name, "emissivity", a.emissivity)?;
+            fraction(&a.name, "emissivity", a.emissivity)?;
+            non_negative(&a.name, "kg_per_m2", a.kg_per_m2)?;
+        }
+        for w in &self.windows {
+            validate_window(w)?;
+        }
+        // the model's Uw comes out a lot lower than the measured mylar tables, so ranking one
+        // against the other would just pick the modelled window
+        let modelled = self.windows.iter().find(|w| w.glazing.is_some());
+        let measured = self.windows.iter().find(|w| w.glazing.is_none());
+        if let (Some(modelled), Some(measured)) = (modelled, measured) {
+            return Err(LoadError::Invalid(format!(
+                "windows: {modelled} has glazing and {measured} has a uw_table, modelled and \
+                 measured windows can't be compared so they need separate catalogs"
+            )));
+        }
+        for r in &self.reflective_materials {
+            non_negative(&r.name, "cost_per_m2", r.cost_per_m2)?;
+            fraction(&r.name, "reflectivity", r.reflectivity)?;
+            non_negative(&r.name, "kg_per_m2", r.kg_per_m2)?;
+        }
+        for i in &self.insulators {
+            non_negative(&i.name, "cost_per_m3", i.cost_per_m3)?;
+            positive(&i.name, "conductivity", i.conductivity)?;
+            non_negative(&i.name, "kg_per_m3", i.kg_per_m3)?;
+        }
+        for b in &self.body_materials {
+            non_negative(&b.name, "cost_per_m2", b.cost_per_m2)?;
+            positive(&b.name, "conductivity", b.conductivity)?;
+            positive(&b.name, "thickness_in", b.thickness_in)?;
+            non_negative(&b.name, "kg_per_m3", b.kg_per_m3)?;
+        }
+
+        Ok(())
+    }
+
+    /// every oven these materials can make
+    pub fn ovens(&self) -> Vec<Oven> {
+        self.choices().ovens()
+    }
+
+    /// every oven these materials can make, once for each of `spec`'s starting points
+    pub fn variants(&self, spec: &OptimizationSpec) -> Vec<(Oven, [f64; 3])> {
+        let starts = spec.starts();
+
+        self.ovens()
+            .into_iter()
+            .flat_map(|oven| starts.iter().map(move |&init| (oven.clone(), init)))
+            .collect()
+    }
+}
+
+fn validate_window(w: &WindowSpec) -> Result<(), LoadError> {
+    non_negative(&w.name, "cost_per_m2", w.cost_per_m2)?;
+    non_negative(&w.name, "kg_per_m2", w.kg_per_m2)?;
+    positive(&w.name, "layers", w.layers as f64)?;
+    positive(&w.name, "emissivity", w.emissivity)?;
+    fraction(&w.name, "emissivity", w.emissivity)?;
+    if !w.refractive_index.is_finite() || w.refractive_index < 1. {
+        return Err(LoadError::Invalid(format!(
+            "{}: refractive_index must be >= 1, got {}",
+            w.name, w.refractive_index
+        )));
+    }
+    non_negative(&w.name, "extinction", w.extinction)?;
+    positive(&w.name, "sheet_thickness_mm", w.sheet_thickness_mm)?;
+
+    match (&w.glazing, w.uw_table.is_empty()) {
+        (Some(glazing), true) => {
+            if w.layers > 1 {
+                positive(&w.name, "gap_mm", glazing.gap_mm)?;
+            }
+            positive(&w.name, "sheet_conductivity", glazing.sheet_conductivity)?;
+            return Ok(());
+        }
+        (Some(_), false) => {
+            return Err(LoadError::Invalid(format!(
+                "{}: has both a uw_table and glazing, pick one",
+                w.name
+            )));
+        }
+        (None, _) => {}
+    }
+
+    if w.uw_table.len() < 2 {
+        return Err(LoadError::Invalid(format!(
+            "{}: uw_table needs at least 2 points to fit a trendline (or use glazing instead)",
+            w.name
+        )));
+    }
+    for &(uw, t) in &w.uw_table {
+        positive(&w.name, "uw_table Uw", uw)?;
+        if !t.is_finite() {
+            return Err(LoadError::Invalid(format!(
+                "{}: uw_table has a temperature of {t}",
+                w.name
+            )));
+        }
+    }
+
+    // a flat line can't be inverted to get Uw back out
+    match w.uw_line() {
+        Ok(line) if line.coefficient.is_finite() && line.coefficient != 0. => Ok(()),
+        _ => Err(LoadError::Invalid(format!(
+            "{}: couldn't fit a trendline through uw_table, it needs at least 2 different Uw \
+             values with temperature changing with them",
+            w.name
+        ))),
+    }
+}
+
+fn unique_names<'a>(
+    category: &str,
+    names: impl Iterator<Item = &'a String>,
+) -> Result<(), LoadError> {
+    let mut seen = HashSet::new();
+    let mut empty = true;
+
+    for name in names {
+        empty = false;
+        if name.trim().is_empty() {
+            return Err(LoadError::Invalid(format!(
+                "{category} has a material with no name"
+            )));
+        }
+        if !seen.insert(name) {
+            return Err(LoadError::Invalid(format!(
+                "{category} has \"{name}\" more than once"
+            )));
+        }
+    }
+
+    if empty {
+        return Err(LoadError::Invalid(format!("{category} is empty")));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Gas, Glazing};
+
+    fn modelled(name: &str) -> WindowSpec {
+        let mut window = WindowMaterial::DoubleMylar.spec();
+        window.name = name.to_string();
+        window.uw_table.clear();
+        window.glazing = Some(Glazing {
+            gap_mm: 10.,
+            gas: Gas::Air,
+            sheet_conductivity: 0.15,
+        });
+
+        window
+    }
+
+    #[test]
+    fn windows_are_all_measured_or_all_modelled() {
+        let mut catalog = Catalog::builtin();
+        assert!(catalog.validate().is_ok());
+
+        catalog.windows.push(modelled("Double Mylar (modelled)"));
+        assert!(catalog.validate().is_err());
+
+        catalog.windows = vec![modelled("A"), modelled("B")];
+        assert!(catalog.validate().is_ok());
+    }
+}
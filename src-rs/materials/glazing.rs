@@ -0,0 +1,288 @@
+//! Uw of a window worked out from how it's built instead of measured, following ISO 15099: heat
+//! goes from the oven to the first sheet, across each gas gap, and from the last sheet to the
+//! outside, by convection and radiation in parallel at every step.
+//!
+//! the coefficients depend on the sheet temperatures, which depend on the heat flow, so the
+//! sheet temperatures are iterated until they stop moving
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Angle, Length, Temperature, UValue, WindowSpec, STEFAN_BOLTZMANN};
+
+/// J/(kmol*K)
+const GAS_CONSTANT: f64 = 8314.462;
+const PRESSURE: f64 = 101325.;
+const GRAVITY: f64 = 9.81;
+
+const MAX_ITERATIONS: usize = 100;
+/// K
+const TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub enum Gas {
+    Air,
+    Argon,
+    Krypton,
+}
+
+/// how a window with more than one sheet is put together. the sheets themselves (count,
+/// thickness, emissivity) come from the `WindowSpec`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct Glazing {
+    // between each pair of sheets
+    pub gap_mm: f64,
+    pub gas: Gas,
+    // W/(m*K) of the sheet material, ~0.15 for mylar, ~1 for glass, ~0.2 for acrylic
+    pub sheet_conductivity: f64,
+}
+
+/// what's on either side of the window
+#[derive(Debug, Clone, Copy)]
+pub struct GlazingConditions {
+    pub inside: Temperature,
+    pub outside: Temperature,
+    // of whatever the window sees inside the oven, the absorber
+    pub inside_emissivity: f64,
+    // m/s
+    pub wind_speed: f64,
+    // from horizontal
+    pub tilt: Angle,
+    // side length of the (square) window
+    pub width: Length,
+}
+
+impl Gas {
+    /// (conductivity W/(m*K), viscosity Pa*s, specific heat J/(kg*K), molar mass kg/kmol) at `t`
+    /// kelvin, from the linear fits in ISO 15099 table B.1
+    fn properties(&self, t: f64) -> (f64, f64, f64, f64) {
+        match self {
+            Gas::Air => (
+                2.873e-3 + 7.76e-5 * t,
+                3.723e-6 + 4.94e-8 * t,
+                1002.737 + 1.2324e-2 * t,
+                28.97,
+            ),
+            Gas::Argon => (
+                2.285e-3 + 5.149e-5 * t,
+                3.379e-6 + 6.451e-8 * t,
+                521.9285,
+                39.948,
+            ),
+            Gas::Krypton => (
+                9.443e-4 + 2.826e-5 * t,
+                2.213e-6 + 7.777e-8 * t,
+                248.0907,
+                83.8,
+            ),
+        }
+    }
+
+    /// Rayleigh number and conductivity of a layer of this gas `thickness` m thick with `hot`
+    /// and `cold` kelvin on either side
+    fn rayleigh(&self, hot: f64, cold: f64, thickness: f64) -> (f64, f64) {
+        let mean = (hot + cold) / 2.;
+        let (k, mu, cp, molar_mass) = self.properties(mean);
+        let density = PRESSURE * molar_mass / (GAS_CONSTANT * mean);
+
+        let ra = density.powi(2) * GRAVITY * cp * (hot - cold).abs() * thickness.powi(3)
+            / (mu * k * mean);
+
+        (ra, k)
+    }
+}
+
+/// Hollands et al. for an inclined layer heated from below (the oven is under the window), only
+/// good up to 75° so steeper windows are treated as 75°
+fn inclined_nusselt(ra: f64, tilt: Angle) -> f64 {
+    let tilt = tilt.value().clamp(0., 75f64.to_radians());
+    let ra_cos = ra * tilt.cos();
+    if ra_cos <= 0. {
+        return 1.;
+    }
+
+    1. + 1.44 * (1. - 1708. / ra_cos).max(0.) * (1. - 1708. * (1.8 * tilt).sin().powf(1.6) / ra_cos)
+        + ((ra_cos / 5830.).cbrt() - 1.).max(0.)
+}
+
+/// radiative coefficient between two grey surfaces at `a` and `b` kelvin, W/(m^2*K)
+fn radiative(a: f64, b: f64, emissivity_a: f64, emissivity_b: f64) -> f64 {
+    STEFAN_BOLTZMANN * (a * a + b * b) * (a + b) / (1. / emissivity_a + 1. / emissivity_b - 1.)
+}
+
+impl Glazing {
+    /// Uw of `window` built this way, including radiation between every layer
+    pub fn u_value(&self, window: &WindowSpec, conditions: &GlazingConditions) -> UValue {
+        let layers = window.layers.max(1) as usize;
+        let emissivity = window.emissivity;
+        let gap = self.gap_mm / 1000.;
+        let sheet = window.sheet_thickness().value() / self.sheet_conductivity;
+
+        let inside = conditions.inside.as_kelvin();
+        let outside = conditions.outside.as_kelvin();
+        if (inside - outside).abs() < TOLERANCE {
+            // no heat flow to get coefficients from, nudge it
+            let nudged = GlazingConditions {
+                inside: Temperature::kelvin(outside + 1.),
+                ..*conditions
+            };
+            return self.u_value(window, &nudged);
+        }
+
+        // the oven side is heated from below too, the characteristic length of a horizontal
+        // plate is its area over its perimeter
+        let length = conditions.width.value() / 4.;
+        let outside_convection = 4. + 4. * conditions.wind_speed;
+
+        let mut sheets: Vec<f64> = (1..=layers)
+            .map(|i| inside + (outside - inside) * i as f64 / (layers + 1) as f64)
+            .collect();
+        let mut resistance = 0.;
+
+        for _ in 0..MAX_ITERATIONS {
+            let first = sheets[0];
+            let last = sheets[layers - 1];
+
+            let (ra, k) = Gas::Air.rayleigh(inside, first, length);
+            let inside_convection = if ra < 1e7 {
+                0.54 * ra.powf(0.25)
+            } else {
+                0.15 * ra.cbrt()
+            } * k
+                / length;
+            let inside_film = 1.
+                / (inside_convection
+                    + radiative(inside, first, conditions.inside_emissivity, emissivity));
+
+            let gaps: Vec<f64> = sheets
+                .windows(2)
+                .map(|pair| {
+                    let (ra, k) = self.gas.rayleigh(pair[0], pair[1], gap);
+                    let convection = inclined_nusselt(ra, conditions.tilt) * k / gap;
+
+                    1. / (convection + radiative(pair[0], pair[1], emissivity, emissivity))
+                })
+                .collect();
+
+            let outside_film = 1.
+                / (outside_convection
+                    + emissivity
+                        * STEFAN_BOLTZMANN
+                        * (last * last + outside * outside)
+                        * (last + outside));
+
+            resistance =
+                inside_film + gaps.iter().sum::<f64>() + sheet * layers as f64 + outside_film;
+            let q = (inside - outside) / resistance;
+
+            // walk the heat flow through the layers to get new sheet temperatures
+            let mut next = Vec::with_capacity(layers);
+            let mut t = inside - q * inside_film;
+            next.push(t);
+            for gap in &gaps {
+                t -= q * (sheet + gap);
+                next.push(t);
+            }
+
+            let change = next
+                .iter()
+                .zip(&sheets)
+                .map(|(a, b)| (a - b).abs())
+                .fold(0., f64::max);
+            sheets = next;
+
+            if change < TOLERANCE {
+                break;
+            }
+        }
+
+        UValue::watts_per_square_meter_kelvin(resistance.recip())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WindowMaterial;
+
+    fn within(value: f64, expected: f64, fraction: f64) -> bool {
+        (value - expected).abs() <= fraction * expected.abs()
+    }
+
+    /// the ISO fits against the usual tables at 300 K (Incropera, air: k 0.0263, μ 1.846e-5,
+    /// cp 1007, argon: k 0.0177)
+    #[test]
+    fn gas_properties_match_tables() {
+        let (k, mu, cp, _) = Gas::Air.properties(300.);
+        assert!(within(k, 0.0263, 0.01), "{k}");
+        assert!(within(mu, 1.846e-5, 0.01), "{mu}");
+        assert!(within(cp, 1007., 0.01), "{cp}");
+
+        let (k, ..) = Gas::Argon.properties(300.);
+        assert!(within(k, 0.0177, 0.01), "{k}");
+    }
+
+    /// 4σT^3 between black surfaces, about 6.1 W/(m^2*K) at room temperature
+    #[test]
+    fn radiative_coefficient() {
+        let black = 4. * STEFAN_BOLTZMANN * 300f64.powi(3);
+        assert!(within(radiative(300., 300., 1., 1.), black, 1e-12));
+        assert!(within(black, 6.124, 1e-3));
+
+        let glass = radiative(300., 300., 0.84, 0.84);
+        assert!(within(glass, black / (2. / 0.84 - 1.), 1e-12));
+    }
+
+    /// below Ra = 1708 a layer heated from below doesn't move and only conducts
+    #[test]
+    fn still_layers_only_conduct() {
+        assert_eq!(inclined_nusselt(1000., Angle::degrees(0.)), 1.);
+        // tilting it 60° halves how hard it's pushed
+        assert_eq!(inclined_nusselt(3000., Angle::degrees(60.)), 1.);
+        assert!(inclined_nusselt(1e4, Angle::degrees(0.)) > 2.);
+        assert_eq!(
+            inclined_nusselt(1e5, Angle::degrees(80.)),
+            inclined_nusselt(1e5, Angle::degrees(75.))
+        );
+    }
+
+    #[test]
+    fn more_layers_and_heavier_gases_insulate_better() {
+        let conditions = GlazingConditions {
+            inside: Temperature::celsius(150.),
+            outside: Temperature::celsius(21.),
+            inside_emissivity: 0.9,
+            wind_speed: 1.,
+            tilt: Angle::degrees(40.),
+            width: Length::meters(0.1),
+        };
+        let u = |layers: u32, emissivity: f64, gap_mm: f64, gas: Gas| {
+            let mut window = WindowMaterial::SingleMylar.spec();
+            window.layers = layers;
+            window.emissivity = emissivity;
+            window.sheet_thickness_mm = 4.;
+            let glazing = Glazing {
+                gap_mm,
+                gas,
+                sheet_conductivity: 1.,
+            };
+
+            glazing.u_value(&window, &conditions).value()
+        };
+
+        let single = u(1, 0.84, 12., Gas::Air);
+        let air = u(2, 0.84, 12., Gas::Air);
+        let argon = u(2, 0.84, 12., Gas::Argon);
+        let krypton = u(2, 0.84, 12., Gas::Krypton);
+        assert!(single > air && air > argon && argon > krypton);
+        assert!(u(2, 0.1, 12., Gas::Air) < air);
+
+        // with no radiation, a thin gap adds about its still air and one more sheet. the films
+        // shift a little with the sheet temperatures, so it's not exact
+        let added = 1. / u(2, 1e-6, 1., Gas::Air) - 1. / u(1, 1e-6, 1., Gas::Air);
+        let mean = (conditions.inside.as_kelvin() + conditions.outside.as_kelvin()) / 2.;
+        let (k, ..) = Gas::Air.properties(mean);
+        assert!(within(added, 0.001 / k + 0.004, 0.15), "{added}");
+    }
+}
@@ -29,7 +29,7 @@ macro_rules! common {
 
 #[macro_export]
 macro_rules! impl_absorber {
-    ($($name:ident, $pretty:expr, $per_m2:expr, $absorp:expr);+) => {
+    ($($name:ident, $pretty:expr, $per_m2:expr, $absorp:expr, $emissivity:expr, $kg_per_m2:expr);+) => {
         $crate::common!(Absorber, $($name, $pretty);+);
 
         impl Absorber {
@@ -41,20 +41,42 @@ macro_rules! impl_absorber {
                 }
             }
 
-            pub fn cost_per_m2(&self, m2: f64) -> f64 {
+            pub fn emissivity(&self) -> f64 {
                 match self {
                     $(
-                        Self::$name => $per_m2 * m2,
+                        Self::$name => $emissivity,
                     )+
                 }
             }
+
+            pub fn cost_per_m2(&self, area: $crate::Area) -> f64 {
+                match self {
+                    $(
+                        Self::$name => $per_m2 * area.value(),
+                    )+
+                }
+            }
+
+            pub fn spec(&self) -> $crate::AbsorberSpec {
+                $crate::AbsorberSpec {
+                    name: self.to_string(),
+                    cost_per_m2: match self {
+                        $(Self::$name => $per_m2,)+
+                    },
+                    absorptivity: self.absoptivity(),
+                    emissivity: self.emissivity(),
+                    kg_per_m2: match self {
+                        $(Self::$name => $kg_per_m2,)+
+                    },
+                }
+            }
         }
     };
 }
 
 #[macro_export]
 macro_rules! impl_reflective {
-    ($($name:ident, $pretty:expr, $per_m2:expr, $reflectivity:expr);+) => {
+    ($($name:ident, $pretty:expr, $per_m2:expr, $reflectivity:expr, $kg_per_m2:expr);+) => {
         $crate::common!(ReflectiveMaterial, $($name, $pretty);+);
 
         impl ReflectiveMaterial {
@@ -66,74 +88,113 @@ macro_rules! impl_reflective {
                 }
             }
 
-            pub fn cost_per_m2(&self, m2: f64) -> f64 {
+            pub fn cost_per_m2(&self, area: $crate::Area) -> f64 {
                 match self {
                     $(
-                        Self::$name => $per_m2 * m2,
+                        Self::$name => $per_m2 * area.value(),
                     )+
                 }
             }
+
+            pub fn spec(&self) -> $crate::ReflectiveSpec {
+                $crate::ReflectiveSpec {
+                    name: self.to_string(),
+                    cost_per_m2: match self {
+                        $(Self::$name => $per_m2,)+
+                    },
+                    reflectivity: self.reflectivity(),
+                    kg_per_m2: match self {
+                        $(Self::$name => $kg_per_m2,)+
+                    },
+                }
+            }
         }
     };
 }
 
 #[macro_export]
 macro_rules! impl_insulator {
-    ($($name:ident, $pretty:expr, $per_m2:expr, $conductivity:expr);+) => {
+    ($($name:ident, $pretty:expr, $per_m2:expr, $conductivity:expr, $kg_per_m3:expr);+) => {
         $crate::common!(Insulator, $($name, $pretty);+);
 
         impl Insulator {
-            pub fn conductivity(&self) -> f64 {
+            pub fn conductivity(&self) -> $crate::Conductivity {
                 match self {
                     $(
-                        Self::$name => $conductivity,
+                        Self::$name => $crate::Conductivity::watts_per_meter_kelvin($conductivity),
                     )+
                 }
             }
 
-            pub fn cost_per_m3(&self, vol: f64) -> f64 {
+            pub fn cost_per_m3(&self, volume: $crate::Volume) -> f64 {
                 match self {
                     $(
-                        Self::$name => $per_m2 * vol,
+                        Self::$name => $per_m2 * volume.value(),
                     )+
                 }
             }
+
+            pub fn spec(&self) -> $crate::InsulatorSpec {
+                $crate::InsulatorSpec {
+                    name: self.to_string(),
+                    cost_per_m3: match self {
+                        $(Self::$name => $per_m2,)+
+                    },
+                    conductivity: self.conductivity().value(),
+                    kg_per_m3: match self {
+                        $(Self::$name => $kg_per_m3,)+
+                    },
+                }
+            }
         }
     };
 }
 
 #[macro_export]
 macro_rules! impl_body {
-    ($($name:ident, $pretty:expr, $per_m2:expr, $conductivity:expr, $thick:expr);+) => {
+    ($($name:ident, $pretty:expr, $per_m2:expr, $conductivity:expr, $thick:expr, $kg_per_m3:expr);+) => {
         $crate::common!(BodyMaterial, $($name, $pretty);+);
 
         impl BodyMaterial {
-            pub fn conductivity(&self) -> f64 {
+            pub fn conductivity(&self) -> $crate::Conductivity {
                 match self {
                     $(
-                        Self::$name => $conductivity,
+                        Self::$name => $crate::Conductivity::watts_per_meter_kelvin($conductivity),
                     )+
                 }
             }
 
-            pub fn cost_per_m2(&self, m2: f64) -> f64 {
+            pub fn cost_per_m2(&self, area: $crate::Area) -> f64 {
                 match self {
                     $(
-                        Self::$name => $per_m2 * m2,
+                        Self::$name => $per_m2 * area.value(),
                     )+
                 }
             }
 
-            pub fn thickness(&self) -> f64 {
+            /// thicknesses in the table are in inches
+            pub fn thickness(&self) -> $crate::Length {
                 match self {
                     $(
-                        Self::$name => $thick,
+                        Self::$name => $crate::Length::inches($thick),
                     )+
                 }
             }
 
-            pub fn thickness_cm(&self) -> f64 {
-                self.thickness() * 2.54
+            pub fn spec(&self) -> $crate::BodySpec {
+                $crate::BodySpec {
+                    name: self.to_string(),
+                    cost_per_m2: match self {
+                        $(Self::$name => $per_m2,)+
+                    },
+                    conductivity: self.conductivity().value(),
+                    thickness_in: match self {
+                        $(Self::$name => $thick,)+
+                    },
+                    kg_per_m3: match self {
+                        $(Self::$name => $kg_per_m3,)+
+                    },
+                }
             }
         }
     };
@@ -2,8 +2,9 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use wasm_bindgen::prelude::wasm_bindgen;
 
-use crate::LNTrendline;
+use crate::{Angle, Area, Glazing, LNTrendline, Length, Temperature, UValue};
 
+// the built-in windows, see `WindowMaterial::spec`
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[wasm_bindgen]
 #[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
@@ -12,24 +13,63 @@ pub enum WindowMaterial {
     DoubleMylar,
 }
 
-type UWGraph = (f64, f64);
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[wasm_bindgen(getter_with_clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct WindowSpec {
+    pub name: String,
+    // for the whole window, every layer
+    pub cost_per_m2: f64,
+    pub kg_per_m2: f64,
+    pub layers: u32,
+    // thermal infrared emissivity of one layer
+    pub emissivity: f64,
+    // optics of one sheet, for `transmittance`
+    pub refractive_index: f64,
+    // 1/m
+    pub extinction: f64,
+    pub sheet_thickness_mm: f64,
+    // measured (Uw in W/(m^2*K), window temperature in °C) points. Uw at any other temperature
+    // comes from a ln trendline fitted through these
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub uw_table: Vec<(f64, f64)>,
+    // for windows without a `uw_table`, Uw is calculated from how the window is built instead
+    #[wasm_bindgen(skip)]
+    pub glazing: Option<Glazing>,
+    // `uw_table`'s trendline, from the last `fit_uw_line`. `None` if it hasn't been fitted or
+    // can't be
+    #[serde(skip)]
+    uw_line: Option<LNTrendline>,
+}
+
+type UWGraph = (UValue, Temperature);
+
+// one mylar (PET) sheet
+const MYLAR_REFRACTIVE_INDEX: f64 = 1.64;
+// 1/m
+const MYLAR_EXTINCTION: f64 = 100.;
+// 4 mil
+const MYLAR_THICKNESS_MM: f64 = 0.1;
+// kg/m^3
+const MYLAR_DENSITY: f64 = 1390.;
 
 impl WindowMaterial {
     pub fn variants() -> &'static [Self] {
         &[Self::SingleMylar, Self::DoubleMylar]
     }
-    pub fn cost_per_m2(&self, m2: f64) -> f64 {
+
+    fn layers(&self) -> u32 {
         match self {
-            // TODO: find out the size of the mylar sheets
-            WindowMaterial::SingleMylar => 4.1443933155 * m2,
-            WindowMaterial::DoubleMylar =>  4.1443933155 * 2. * m2,
+            WindowMaterial::SingleMylar => 1,
+            WindowMaterial::DoubleMylar => 2,
         }
     }
 
-    /// Vec<(Uw, Temperature)>
-    pub fn uws(&self) -> Vec<UWGraph> {
+    // Uw, temperature
+    fn uw_table(&self) -> &'static [(f64, f64)] {
         match self {
-            WindowMaterial::SingleMylar => vec![
+            WindowMaterial::SingleMylar => &[
                 (10.10, 66.),
                 (13.90, 93.),
                 (18.66, 121.),
@@ -37,7 +77,7 @@ impl WindowMaterial {
                 (31.60, 177.),
                 (40.11, 204.),
             ],
-            WindowMaterial::DoubleMylar => vec![
+            WindowMaterial::DoubleMylar => &[
                 (4.88, 66.),
                 (6.69, 93.),
                 (8.96, 121.),
@@ -48,24 +88,156 @@ impl WindowMaterial {
         }
     }
 
-    /// y = temperature, x = uw
-    pub fn uw_line(&self) -> LNTrendline {
-        match self {
-            WindowMaterial::SingleMylar => LNTrendline {
-                coefficient: 100.5092939,
-                intercept: -170.0073519,
-            },
-            WindowMaterial::DoubleMylar => LNTrendline {
-                coefficient: 100.5953534,
-                intercept: -96.79716142,
-            },
+    pub fn spec(&self) -> WindowSpec {
+        let mut spec = WindowSpec {
+            name: self.to_string(),
+            // TODO: find out the size of the mylar sheets
+            cost_per_m2: 4.1443933155 * self.layers() as f64,
+            kg_per_m2: MYLAR_DENSITY * MYLAR_THICKNESS_MM / 1000. * self.layers() as f64,
+            layers: self.layers(),
+            // mylar is about half as good at radiating as a black surface
+            emissivity: 0.5,
+            refractive_index: MYLAR_REFRACTIVE_INDEX,
+            extinction: MYLAR_EXTINCTION,
+            sheet_thickness_mm: MYLAR_THICKNESS_MM,
+            uw_table: self.uw_table().to_vec(),
+            // measured windows don't need the glazing model
+            glazing: None,
+            uw_line: None,
+        };
+        spec.fit_uw_line();
+
+        spec
+    }
+}
+
+impl WindowSpec {
+    pub fn cost(&self, area: Area) -> f64 {
+        self.cost_per_m2 * area.value()
+    }
+
+    /// kg
+    pub fn mass(&self, area: Area) -> f64 {
+        self.kg_per_m2 * area.value()
+    }
+
+    pub fn sheet_thickness(&self) -> Length {
+        Length::centimeters(self.sheet_thickness_mm / 10.)
+    }
+
+    /// Vec<(Uw, Temperature)>
+    pub fn uws(&self) -> Vec<UWGraph> {
+        self.uw_table
+            .iter()
+            .map(|&(uw, t)| {
+                (
+                    UValue::watts_per_square_meter_kelvin(uw),
+                    Temperature::celsius(t),
+                )
+            })
+            .collect()
+    }
+
+    /// fits `uw_line` through `uw_table`, this has to be done again after changing the table.
+    /// `WindowMaterial::spec` and loading a catalog or design already do it
+    pub fn fit_uw_line(&mut self) {
+        self.uw_line = LNTrendline::fit(&self.uw_table).ok();
+    }
+
+    /// y = temperature, x = uw, fitted through `uw_table`. windows that haven't been through
+    /// `fit_uw_line` get it fitted on every call
+    pub fn uw_line(&self) -> Result<LNTrendline, linreg::Error> {
+        match &self.uw_line {
+            Some(line) => Ok(line.clone()),
+            None => LNTrendline::fit(&self.uw_table),
+        }
+    }
+
+    /// how well `uw_line` matches `uw_table`, 1 is a perfect fit
+    pub fn uw_r_squared(&self) -> Result<f64, linreg::Error> {
+        Ok(self.uw_line()?.r_squared(&self.uw_table))
+    }
+
+    /// Uw at a given window temperature, from the inverse of `uw_line`. NaN if the table can't be
+    /// fitted, `Catalog::validate` doesn't let those through. `Design::uw_at` also handles
+    /// windows with `glazing`
+    pub fn uw_at(&self, t: Temperature) -> UValue {
+        let uw = self
+            .uw_line()
+            .map(|line| line.x_at(t.as_celsius()))
+            .unwrap_or(f64::NAN);
+
+        UValue::watts_per_square_meter_kelvin(uw)
+    }
+
+    /// fraction of sunlight that makes it through the window when it hits at `incidence` from
+    /// the normal.
+    ///
+    /// every sheet reflects at both surfaces (Fresnel) and absorbs a little on the way through,
+    /// light bounces back and forth inside each sheet and between the sheets, and the two
+    /// polarizations are worked out separately and then averaged (sunlight is unpolarized)
+    pub fn transmittance(&self, incidence: Angle) -> f64 {
+        let theta = incidence.value();
+        if !(0. ..std::f64::consts::FRAC_PI_2).contains(&theta) {
+            return 0.;
         }
+
+        let n = self.refractive_index;
+        let refracted = (theta.sin() / n).asin();
+        let absorbed = (-self.extinction * self.sheet_thickness().value() / refracted.cos()).exp();
+
+        let (perpendicular, parallel) = if theta < 1e-6 {
+            let r = ((n - 1.) / (n + 1.)).powi(2);
+            (r, r)
+        } else {
+            (
+                ((refracted - theta).sin() / (refracted + theta).sin()).powi(2),
+                ((refracted - theta).tan() / (refracted + theta).tan()).powi(2),
+            )
+        };
+
+        let through = |r: f64| {
+            let sheet = Sheet::new(r, absorbed);
+            let window = (1..self.layers).fold(sheet, |window, _| window.stack(sheet));
+
+            window.transmittance
+        };
+
+        (through(perpendicular) + through(parallel)) / 2.
     }
 
+    /// transmittance straight on
     pub fn transmitivity(&self) -> f64 {
-        match self {
-            WindowMaterial::SingleMylar => 0.92,
-            WindowMaterial::DoubleMylar => (0.92_f64).powi(2),
+        self.transmittance(Angle::radians(0.))
+    }
+}
+
+/// one polarization through a sheet (or a stack of them), both sides are the same
+#[derive(Debug, Clone, Copy)]
+struct Sheet {
+    transmittance: f64,
+    reflectance: f64,
+}
+
+impl Sheet {
+    /// a sheet with surface reflectance `r` that lets `absorbed` through on one pass
+    fn new(r: f64, absorbed: f64) -> Self {
+        let bounces = 1. - (r * absorbed).powi(2);
+
+        Self {
+            transmittance: absorbed * (1. - r).powi(2) / bounces,
+            reflectance: r + (1. - r).powi(2) * absorbed.powi(2) * r / bounces,
+        }
+    }
+
+    /// `other` behind `self`, with the light bouncing between them
+    fn stack(self, other: Self) -> Self {
+        let bounces = 1. - self.reflectance * other.reflectance;
+
+        Self {
+            transmittance: self.transmittance * other.transmittance / bounces,
+            reflectance: self.reflectance
+                + self.transmittance.powi(2) * other.reflectance / bounces,
         }
     }
 }
@@ -78,3 +250,98 @@ impl Display for WindowMaterial {
         }
     }
 }
+
+impl Display for WindowSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uw_line_follows_the_table() {
+        let mut window = WindowMaterial::DoubleMylar.spec();
+        let before = window.uw_line().unwrap();
+
+        for (uw, _) in &mut window.uw_table {
+            *uw *= 2.;
+        }
+        assert_eq!(window.uw_line().unwrap(), before);
+
+        window.fit_uw_line();
+        let after = window.uw_line().unwrap();
+
+        // doubling every Uw moves T = a ln(Uw) + b down by a ln(2)
+        assert!((after.coefficient - before.coefficient).abs() < 1e-9);
+        assert!((before.intercept - after.intercept - before.coefficient * 2f64.ln()).abs() < 1e-9);
+    }
+
+    /// a mylar window that doesn't absorb anything, only reflects
+    fn clear(layers: u32) -> WindowSpec {
+        WindowSpec {
+            layers,
+            extinction: 0.,
+            ..WindowMaterial::SingleMylar.spec()
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    /// straight on, n clear sheets let (1 - r) / (1 + (2n - 1) r) through (Stokes), one sheet is
+    /// (1 - r)^2 / (1 - r^2)
+    #[test]
+    fn clear_sheets_straight_on() {
+        let r = ((MYLAR_REFRACTIVE_INDEX - 1.) / (MYLAR_REFRACTIVE_INDEX + 1.)).powi(2);
+
+        assert!(close(
+            clear(1).transmitivity(),
+            (1. - r).powi(2) / (1. - r * r)
+        ));
+        for n in 1..=4 {
+            let stokes = (1. - r) / (1. + (2. * n as f64 - 1.) * r);
+            assert!(close(clear(n).transmitivity(), stokes), "{n} sheets");
+        }
+    }
+
+    /// at brewster's angle the parallel polarization isn't reflected at all
+    #[test]
+    fn brewsters_angle() {
+        let n = MYLAR_REFRACTIVE_INDEX;
+        let rs = ((n * n - 1.) / (n * n + 1.)).powi(2);
+        let perpendicular = (1. - rs) / (1. + rs);
+
+        let brewster = Angle::radians(n.atan());
+        assert!(close(
+            clear(1).transmittance(brewster),
+            (perpendicular + 1.) / 2.
+        ));
+    }
+
+    #[test]
+    fn absorbing_sheet() {
+        let window = WindowMaterial::SingleMylar.spec();
+        let r = ((MYLAR_REFRACTIVE_INDEX - 1.) / (MYLAR_REFRACTIVE_INDEX + 1.)).powi(2);
+        // beer-lambert, once through
+        let a = (-MYLAR_EXTINCTION * MYLAR_THICKNESS_MM / 1000.).exp();
+
+        let expected = a * (1. - r).powi(2) / (1. - (a * r).powi(2));
+        assert!(close(window.transmitivity(), expected));
+
+        // stacking a sheet with itself is the same as a 2 layer window
+        let sheet = Sheet::new(r, a);
+        let double = WindowMaterial::DoubleMylar.spec();
+        assert!(close(
+            double.transmitivity(),
+            sheet.stack(sheet).transmittance
+        ));
+        assert!(double.transmitivity() < window.transmitivity());
+
+        assert_eq!(window.transmittance(Angle::degrees(90.)), 0.);
+        assert!(window.transmittance(Angle::degrees(60.)) < window.transmitivity());
+    }
+}
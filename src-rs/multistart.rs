@@ -0,0 +1,319 @@
+//! optimizing every oven from a spread of starting points instead of just `initials`, so a
+//! result that depends on where the optimizer started shows up.
+//!
+//! the extra points fill the bounds with a latin hypercube or a sobol sequence, every start runs
+//! in parallel and the best one wins
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::{
+    optimizers::Rng, Constraints, Environment, Objective, OptimizationResult, OptimizationSpec,
+    Oven,
+};
+
+/// how close (relative to the best score) a start has to get to count as agreeing with it
+pub(crate) const AGREE: f64 = 1e-4;
+
+/// (degree, a, m) of the primitive polynomials after the first dimension, from Joe and Kuo's
+/// new-joe-kuo-6.21201. the first dimension is just the van der Corput sequence
+const SOBOL_POLYNOMIALS: &[(u32, u32, &[u32])] = &[
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub enum Sampling {
+    // every x is split into `starts` strips and each strip gets exactly one point
+    #[default]
+    LatinHypercube,
+    // low discrepancy, fills the space evenly for any number of points. up to 8 dimensions
+    Sobol,
+}
+
+/// the extra starting points, on top of `OptimizationSpec::initials`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct MultiStart {
+    pub starts: u32,
+    pub sampling: Sampling,
+    // the same seed always gives the same points
+    pub seed: u32,
+}
+
+impl Default for MultiStart {
+    fn default() -> Self {
+        Self {
+            starts: 16,
+            sampling: Sampling::default(),
+            seed: 0,
+        }
+    }
+}
+
+impl MultiStart {
+    /// `starts` points inside `bounds`
+    pub fn points(&self, bounds: &[(f64, f64)]) -> Vec<Vec<f64>> {
+        let n = self.starts as usize;
+        let mut rng = Rng::new(self.seed as u64);
+
+        // in [0, 1) along each x
+        let unit: Vec<Vec<f64>> = match self.sampling {
+            Sampling::LatinHypercube => {
+                let strips: Vec<Vec<usize>> = bounds
+                    .iter()
+                    .map(|_| {
+                        // fisher-yates
+                        let mut strips: Vec<usize> = (0..n).collect();
+                        for i in (1..n).rev() {
+                            strips.swap(i, rng.below(i + 1));
+                        }
+                        strips
+                    })
+                    .collect();
+
+                (0..n)
+                    .map(|i| {
+                        strips
+                            .iter()
+                            .map(|strips| (strips[i] as f64 + rng.next_f64()) / n as f64)
+                            .collect()
+                    })
+                    .collect()
+            }
+            Sampling::Sobol => {
+                let directions: Vec<[u32; 32]> = (0..bounds.len()).map(sobol_directions).collect();
+                // a random digital shift, so the seed changes the points and the first one isn't
+                // the corner of the bounds
+                let shifts: Vec<u32> = bounds.iter().map(|_| rng.next_u64() as u32).collect();
+
+                (0..n as u32)
+                    .map(|i| {
+                        directions
+                            .iter()
+                            .zip(&shifts)
+                            .map(|(v, shift)| (sobol(v, i) ^ shift) as f64 / (1u64 << 32) as f64)
+                            .collect()
+                    })
+                    .collect()
+            }
+        };
+
+        unit.into_iter()
+            .map(|x| {
+                x.into_iter()
+                    .zip(bounds)
+                    .map(|(x, (min, max))| min + x * (max - min))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// the `i`th point along one dimension of the sequence, out of 2^32
+fn sobol(directions: &[u32; 32], i: u32) -> u32 {
+    (0..32)
+        .filter(|bit| i >> bit & 1 == 1)
+        .fold(0, |x, bit| x ^ directions[bit])
+}
+
+/// the direction numbers of dimension `d`, scaled up to 32 bits
+fn sobol_directions(d: usize) -> [u32; 32] {
+    let mut v = [0u32; 32];
+    if d == 0 {
+        for (k, v) in v.iter_mut().enumerate() {
+            *v = 1 << (31 - k);
+        }
+        return v;
+    }
+
+    let &(s, a, m) = SOBOL_POLYNOMIALS
+        .get(d - 1)
+        .expect("sobol sampling only goes up to 8 dimensions");
+    let s = s as usize;
+    for k in 0..32 {
+        v[k] = if k < s {
+            m[k] << (31 - k)
+        } else {
+            let mut x = v[k - s] ^ (v[k - s] >> s);
+            for j in 1..s {
+                x ^= ((a >> (s - 1 - j)) & 1) * v[k - j];
+            }
+            x
+        };
+    }
+
+    v
+}
+
+/// "<starts>", "<starts>:lhs" or "<starts>:sobol"
+impl FromStr for MultiStart {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (starts, sampling) = match s.split_once(':') {
+            Some((starts, sampling)) => (starts, Some(sampling)),
+            None => (s, None),
+        };
+        let starts = match starts.parse::<u32>() {
+            Ok(starts @ 1..) => starts,
+            _ => return Err(format!("bad number of starts in \"{s}\"")),
+        };
+        let sampling = match sampling {
+            None | Some("lhs") => Sampling::LatinHypercube,
+            Some("sobol") => Sampling::Sobol,
+            Some(other) => {
+                return Err(format!(
+                    "unknown sampling \"{other}\", expected lhs or sobol"
+                ))
+            }
+        };
+
+        Ok(Self {
+            starts,
+            sampling,
+            ..Default::default()
+        })
+    }
+}
+
+/// the best of an oven's starts, and how many of the others got there too
+#[derive(Debug, Clone)]
+pub struct MultiStartResult {
+    pub best: OptimizationResult,
+    pub starts: u32,
+    // starts (including the best) that ended within `AGREE` of the best score
+    pub agreed: u32,
+    // starts where the optimizer failed
+    pub failed: u32,
+}
+
+impl MultiStartResult {
+    pub fn disagreed(&self) -> bool {
+        self.agreed < self.starts
+    }
+}
+
+impl Oven {
+    /// `best_design` from each of `starts` in parallel, keeping the best. a design inside
+    /// `constraints` always beats one outside them, and anything beats a failed start
+    pub fn multi_start(
+        &self,
+        starts: &[[f64; 3]],
+        env: &Environment,
+        objective: &dyn Objective,
+        constraints: &Constraints,
+        spec: &OptimizationSpec,
+    ) -> MultiStartResult {
+        let mut results: Vec<(OptimizationResult, bool)> = starts
+            .par_iter()
+            .map(|&init| {
+                let result = self
+                    .clone()
+                    .best_design(init, env, objective, constraints, spec);
+                let feasible = constraints.feasible(&result.design, env);
+
+                (result, feasible)
+            })
+            .collect();
+
+        // working first, then feasible, then the lowest score
+        let rank = |(result, feasible): &(OptimizationResult, bool)| {
+            (result.failed(), !feasible, result.score)
+        };
+        let best = (0..results.len())
+            .min_by(|&a, &b| {
+                let (a, b) = (rank(&results[a]), rank(&results[b]));
+                (a.0, a.1).cmp(&(b.0, b.1)).then(a.2.total_cmp(&b.2))
+            })
+            .expect("there's always at least one start");
+
+        let (failed, best_infeasible, best_score) = rank(&results[best]);
+        let agreed = results
+            .iter()
+            .map(rank)
+            .filter(|&(failed, infeasible, score)| {
+                (failed, infeasible) == (false, best_infeasible)
+                    && (score - best_score).abs() <= AGREE * best_score.abs().max(1.)
+            })
+            .count() as u32;
+
+        MultiStartResult {
+            failed: results.iter().filter(|(result, _)| result.failed()).count() as u32,
+            // a failed best doesn't agree with itself
+            agreed: if failed { 0 } else { agreed.max(1) },
+            best: results.swap_remove(best).0,
+            starts: starts.len() as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the first 8 points of the 3 dimensional sequence, as scipy's `Sobol(3, scramble=False)`
+    /// gives them. scipy goes through them in gray code order, `sobol` in plain binary order,
+    /// which is the same points in every power of 2 block
+    #[test]
+    fn first_points_match_joe_kuo() {
+        let expected = [
+            [0., 0., 0.],
+            [0.5, 0.5, 0.5],
+            [0.75, 0.25, 0.25],
+            [0.25, 0.75, 0.75],
+            [0.375, 0.375, 0.625],
+            [0.875, 0.875, 0.125],
+            [0.625, 0.125, 0.875],
+            [0.125, 0.625, 0.375],
+        ];
+        let directions: Vec<[u32; 32]> = (0..3).map(sobol_directions).collect();
+
+        for (i, point) in expected.iter().enumerate() {
+            let gray = (i ^ i >> 1) as u32;
+            for (v, &x) in directions.iter().zip(point) {
+                assert_eq!(sobol(v, gray) as f64 / (1u64 << 32) as f64, x, "point {i}");
+            }
+        }
+    }
+
+    /// the first dimension is van der Corput, reversing the bits of i
+    #[test]
+    fn first_dimension_is_van_der_corput() {
+        let v = sobol_directions(0);
+        for i in 0..1024 {
+            assert_eq!(sobol(&v, i), i.reverse_bits());
+        }
+    }
+
+    /// 2^k shifted points still put exactly one point in each 1/2^k strip of every x
+    #[test]
+    fn shifted_points_are_stratified() {
+        let bounds = [(0.01, 0.4), (0., 0.1), (1., 3.), (0., 1.), (-1., 1.)];
+        for seed in 0..4 {
+            let points = MultiStart {
+                starts: 16,
+                sampling: Sampling::Sobol,
+                seed,
+            }
+            .points(&bounds);
+
+            for (d, &(min, max)) in bounds.iter().enumerate() {
+                let mut strips: Vec<usize> = points
+                    .iter()
+                    .map(|p| ((p[d] - min) / (max - min) * 16.) as usize)
+                    .collect();
+                strips.sort();
+                assert_eq!(strips, (0..16).collect::<Vec<_>>(), "seed {seed}, x{d}");
+            }
+        }
+    }
+}
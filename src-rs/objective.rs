@@ -0,0 +1,175 @@
+//! what "best" means to the optimizer. every objective is minimized, so the ones that want a high
+//! number return it negated
+
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+use crate::{Design, Environment, Temperature};
+
+/// how badly breaking a budget or temperature limit is penalized, per $ or per °C. cobyla works
+/// better with a steep penalty than with a cliff
+const PENALTY: f64 = 100.;
+
+pub trait Objective: Sync {
+    /// lower is better
+    fn score(&self, design: &Design, env: &Environment) -> f64;
+}
+
+/// get as close to `env.goal_tio` as possible, with every $ counting as `cost_weight` °C
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct GoalDistance {
+    pub cost_weight: f64,
+}
+
+/// the most °C above ambient per $
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct PerformanceIndex;
+
+/// as hot as possible without costing more than `budget` $
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct MaxTempUnderBudget {
+    pub budget: f64,
+}
+
+/// as cheap as possible while still getting to `min_tio`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct MinCostAboveTemp {
+    pub min_tio: Temperature,
+}
+
+impl Default for GoalDistance {
+    fn default() -> Self {
+        Self { cost_weight: 2. }
+    }
+}
+
+impl Objective for GoalDistance {
+    fn score(&self, design: &Design, env: &Environment) -> f64 {
+        let tio = design.predicted_tio(env);
+        let cost = design.total_cost();
+
+        (env.goal_tio - tio).abs().value() + cost * self.cost_weight
+    }
+}
+
+impl Objective for PerformanceIndex {
+    fn score(&self, design: &Design, env: &Environment) -> f64 {
+        -design.performance_index(env)
+    }
+}
+
+impl Objective for MaxTempUnderBudget {
+    fn score(&self, design: &Design, env: &Environment) -> f64 {
+        let tio = design.predicted_tio(env);
+        let over = (design.total_cost() - self.budget).max(0.);
+
+        -(tio - env.ambient).value() + over * PENALTY
+    }
+}
+
+impl Objective for MinCostAboveTemp {
+    fn score(&self, design: &Design, env: &Environment) -> f64 {
+        let tio = design.predicted_tio(env);
+        let short = (self.min_tio - tio).value().max(0.);
+
+        design.total_cost() + short * PENALTY
+    }
+}
+
+/// one of the built-in objectives, so it can be picked from the CLI or a JSON file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub enum BuiltinObjective {
+    GoalDistance(GoalDistance),
+    PerformanceIndex(PerformanceIndex),
+    MaxTempUnderBudget(MaxTempUnderBudget),
+    MinCostAboveTemp(MinCostAboveTemp),
+}
+
+impl Default for BuiltinObjective {
+    fn default() -> Self {
+        Self::GoalDistance(GoalDistance::default())
+    }
+}
+
+impl Objective for BuiltinObjective {
+    fn score(&self, design: &Design, env: &Environment) -> f64 {
+        match self {
+            Self::GoalDistance(o) => o.score(design, env),
+            Self::PerformanceIndex(o) => o.score(design, env),
+            Self::MaxTempUnderBudget(o) => o.score(design, env),
+            Self::MinCostAboveTemp(o) => o.score(design, env),
+        }
+    }
+}
+
+/// "goal", "goal:<cost weight>", "performance-index", "budget:<$>" or "min-cost:<°C>"
+impl FromStr for BuiltinObjective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match s.split_once(':') {
+            Some((name, value)) => (name, Some(value)),
+            None => (s, None),
+        };
+        let number = |what: &str| {
+            value
+                .ok_or_else(|| format!("\"{name}\" needs a {what}, like \"{name}:5\""))?
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("bad {what} in \"{s}\""))
+        };
+
+        match name {
+            "goal" => Ok(Self::GoalDistance(match value {
+                Some(_) => GoalDistance {
+                    cost_weight: number("cost weight")?,
+                },
+                None => GoalDistance::default(),
+            })),
+            "performance-index" => Ok(Self::PerformanceIndex(PerformanceIndex)),
+            "budget" => Ok(Self::MaxTempUnderBudget(MaxTempUnderBudget {
+                budget: number("budget")?,
+            })),
+            "min-cost" => Ok(Self::MinCostAboveTemp(MinCostAboveTemp {
+                min_tio: Temperature::celsius(number("temperature")?),
+            })),
+            _ => Err(format!(
+                "unknown objective \"{s}\", expected goal[:<cost weight>], performance-index, \
+                 budget:<$> or min-cost:<°C>"
+            )),
+        }
+    }
+}
+
+// a `BuiltinObjective` for wasm, which can't take enums with data
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[wasm_bindgen]
+pub struct ObjectiveChoice(BuiltinObjective);
+
+#[wasm_bindgen]
+impl ObjectiveChoice {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Objective for ObjectiveChoice {
+    fn score(&self, design: &Design, env: &Environment) -> f64 {
+        self.0.score(design, env)
+    }
+}
+
+/// `{ "type": "MaxTempUnderBudget", "budget": 5 }` etc, see `BuiltinObjective`
+#[wasm_bindgen]
+pub fn objective_from_json(objective: &str) -> Option<ObjectiveChoice> {
+    serde_json::from_str(objective).ok().map(ObjectiveChoice)
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    files::{self, fraction, non_negative, positive, LoadError},
+    BuiltinOptimizer, GeneticSearch, MultiStart, Volume, CHAMBER_VOLUME,
+};
+
+// what the optimizer searches over for every oven: the chamber it has to fit, how far each of
+// (h, insulator_thickness, reflector_ml) can go, where it starts from and how it searches.
+//
+// the default is the class competition, a 1 L chamber. any field missing from a JSON/TOML file
+// falls back to the default
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[wasm_bindgen]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct OptimizationSpec {
+    // m^3, l and w come from this and h
+    #[wasm_bindgen(skip)]
+    pub chamber_volume: Volume,
+    // (min, max) m
+    #[wasm_bindgen(skip)]
+    pub h: (f64, f64),
+    // (min, max) m
+    #[wasm_bindgen(skip)]
+    pub insulator_thickness: (f64, f64),
+    // (min, max) multiples of l and w
+    #[wasm_bindgen(skip)]
+    pub reflector_ml: (f64, f64),
+    // [h, insulator_thickness, reflector_ml], every oven is optimized once from each
+    #[wasm_bindgen(skip)]
+    pub initials: Vec<[f64; 3]>,
+    // more starting points spread over the bounds, on top of the initials
+    #[wasm_bindgen(skip)]
+    pub multi_start: Option<MultiStart>,
+    #[wasm_bindgen(skip)]
+    pub optimizer: BuiltinOptimizer,
+    // how `Catalog::search` picks materials, only used by the search
+    #[wasm_bindgen(skip)]
+    pub search: Option<GeneticSearch>,
+    // keep how the score improved in every `OptimizationResult`
+    pub history: bool,
+}
+
+impl Default for OptimizationSpec {
+    fn default() -> Self {
+        Self {
+            chamber_volume: CHAMBER_VOLUME,
+            h: (5e-2, 0.4),
+            insulator_thickness: (0., 1.5),
+            reflector_ml: (1e-2, 3.),
+            initials: vec![[0.1, 0.1, 3.] /* [0.4, 1.5, 3.] */],
+            multi_start: None,
+            optimizer: BuiltinOptimizer::default(),
+            search: None,
+            history: false,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl OptimizationSpec {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl OptimizationSpec {
+    pub fn from_json(s: &str) -> Result<Self, LoadError> {
+        let spec: Self = files::from_json(s)?;
+        spec.validate()?;
+
+        Ok(spec)
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, LoadError> {
+        let spec: Self = files::from_toml(s)?;
+        spec.validate()?;
+
+        Ok(spec)
+    }
+
+    /// picks the format from the file extension, anything that isn't `.toml` is read as JSON
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let spec: Self = files::load(path)?;
+        spec.validate()?;
+
+        Ok(spec)
+    }
+
+    pub fn validate(&self) -> Result<(), LoadError> {
+        let name = "optimization";
+
+        positive(name, "chamber_volume", self.chamber_volume.value())?;
+
+        positive(name, "h min", self.h.0)?;
+        non_negative(name, "insulator_thickness min", self.insulator_thickness.0)?;
+        positive(name, "reflector_ml min", self.reflector_ml.0)?;
+        for (field, (min, max)) in ["h", "insulator_thickness", "reflector_ml"]
+            .into_iter()
+            .zip(self.bounds())
+        {
+            if !max.is_finite() || max < min {
+                return Err(LoadError::Invalid(format!(
+                    "{name}: {field} max must be >= min, got ({min}, {max})"
+                )));
+            }
+        }
+
+        self.optimizer.validate()?;
+
+        if self.initials.is_empty() {
+            return Err(LoadError::Invalid(format!(
+                "{name}: initials needs at least one starting point"
+            )));
+        }
+        for init in &self.initials {
+            let inside = init
+                .iter()
+                .zip(self.bounds())
+                .all(|(x, (min, max))| (min..=max).contains(x));
+            if !inside {
+                return Err(LoadError::Invalid(format!(
+                    "{name}: initial point {init:?} is outside the bounds"
+                )));
+            }
+        }
+
+        if let Some(multi_start) = &self.multi_start {
+            positive(name, "multi_start starts", multi_start.starts as f64)?;
+        }
+        if let Some(search) = &self.search {
+            if search.population < 2 {
+                return Err(LoadError::Invalid(format!(
+                    "{name}: search population must be at least 2, got {}",
+                    search.population
+                )));
+            }
+            fraction(name, "search mutation", search.mutation)?;
+        }
+
+        Ok(())
+    }
+
+    /// `initials` followed by the `multi_start` points, every oven is optimized from each one
+    pub fn starts(&self) -> Vec<[f64; 3]> {
+        let mut starts = self.initials.clone();
+        if let Some(multi_start) = &self.multi_start {
+            starts.extend(
+                multi_start
+                    .points(&self.bounds())
+                    .into_iter()
+                    .map(|x| [x[0], x[1], x[2]]),
+            );
+        }
+
+        starts
+    }
+
+    /// (min, max) of [h, insulator_thickness, reflector_ml], the way cobyla takes them
+    pub fn bounds(&self) -> [(f64, f64); 3] {
+        [self.h, self.insulator_thickness, self.reflector_ml]
+    }
+}
+
+#[wasm_bindgen]
+pub fn optimization_spec_from_json(spec: &str) -> Option<OptimizationSpec> {
+    OptimizationSpec::from_json(spec).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Catalog, Design};
+
+    #[test]
+    fn example_file_is_the_default() {
+        let spec = OptimizationSpec::from_toml(include_str!("../optimization.toml")).unwrap();
+
+        assert_eq!(spec, OptimizationSpec::default());
+    }
+
+    /// l and w come from the chamber volume, so every h makes the same size chamber
+    #[test]
+    fn designs_fill_the_chamber() {
+        let oven = &Catalog::builtin().choices().nth(0).unwrap();
+        let volume = Volume::liters(2.);
+
+        for h in [0.05, 0.1, 0.4] {
+            let input: &[f64] = &[h, 0.05, 3.];
+            let design: Design = (oven, input, volume).into();
+
+            assert!((design.chamber_volume().as_liters() - 2.).abs() < 1e-9);
+            assert!((design.l_and_w.value().powi(2) * h - 0.002).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn bounds_are_checked() {
+        let backwards = OptimizationSpec {
+            h: (0.4, 0.05),
+            ..Default::default()
+        };
+        assert!(backwards.validate().is_err());
+
+        let outside = OptimizationSpec {
+            initials: vec![[0.5, 0.1, 3.]],
+            ..Default::default()
+        };
+        assert!(outside.validate().is_err());
+
+        let empty = OptimizationSpec {
+            chamber_volume: Volume::cubic_meters(0.),
+            ..Default::default()
+        };
+        assert!(empty.validate().is_err());
+    }
+}
@@ -1,40 +1,931 @@
-mod export;
-
 use indicatif::ProgressBar;
+use itertools::Itertools;
 use rayon::prelude::*;
+use serde::Serialize;
 use solar_oven::*;
-use std::sync::{Arc, Mutex};
+use std::{
+    cmp::Reverse,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+/// println! for text output, on stderr otherwise so stdout is just the JSON/CSV
+macro_rules! note {
+    ($format:expr, $($arg:tt)*) => {
+        if $format == Format::Text {
+            println!($($arg)*);
+        } else {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!(
+                "unknown format \"{s}\", expected text, json or csv"
+            )),
+        }
+    }
+}
+
+/// one variant's design, flattened for CSV (and JSON from --format)
+#[derive(Debug, Serialize)]
+struct Row {
+    // empty when it's outside the constraints or failed
+    rank: Option<usize>,
+    score: f64,
+    // $
+    cost: f64,
+    // °C
+    tio: f64,
+    performance_index: f64,
+    absorber: String,
+    window: String,
+    outer_body: String,
+    inner_body: String,
+    insulator: String,
+    reflective_material: String,
+    reflector_number: u8,
+    reflector_type: String,
+    l_and_w_cm: f64,
+    h_cm: f64,
+    insulator_thickness_cm: f64,
+    reflector_ml: f64,
+    feasible: bool,
+    status: String,
+    evaluations: u32,
+    // each active constraint and how far inside it the design is, "cost $1.50; weight 0.200 kg"
+    margins: String,
+}
+
+/// one variant for --export to JSON, with the whole design so it can be read back
+#[derive(Debug, Serialize)]
+struct Exported {
+    rank: Option<usize>,
+    score: f64,
+    // $
+    cost: f64,
+    cost_breakdown: CostBreakdown,
+    // °C
+    tio: f64,
+    performance_index: f64,
+    feasible: bool,
+    status: String,
+    evaluations: u32,
+    // negative when it's broken
+    constraints: Vec<Margin>,
+    design: Design,
+}
+
+const USAGE: &str = "\
+usage: optimize [catalog] [options]
+       optimize --pareto <out.json> [catalog] [options]
+       optimize evaluate <design> [options]
+
+all files can be JSON or TOML. see the README for what each option does
+
+every command:
+  --environment <file>
+  --location <lat,lon> --time <local time>
+  --constraints <file>
+  --format text|json|csv          (text or json for evaluate, only text for --pareto)
+
+optimize and evaluate:
+  --objective goal[:<cost weight>]|performance-index|budget:<$>|min-cost:<°C>
+  --heat-up <file>
+  --day <file>
+
+optimize and --pareto:
+  --optimization <file>
+  --optimizer cobyla|nelder-mead|differential-evolution[:<seed>]|grid[:<steps>]
+  --multi-start <starts>[:lhs|:sobol]
+  --include <category>=<name>[,<name>...]
+  --exclude <category>=<name>[,<name>...]
+  --reflector-number <n>
+  --reflector-type <type>
+  --threads <n>
+
+optimize:
+  --search
+  --history
+  --results <n>
+  --export <out.csv|out.json>     (can be repeated)";
+
+/// what the command line asked for
+enum Command {
+    // the best designs by the objective
+    Optimize,
+    // the cost vs temperature trade-off, written to this file
+    Pareto(String),
+    // one design, without optimizing anything
+    Evaluate(Box<Design>),
+}
+
+impl Command {
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Optimize => "optimize",
+            Command::Pareto(_) => "--pareto",
+            Command::Evaluate(_) => "evaluate",
+        }
+    }
+
+    /// options that wouldn't do anything, so they're an error instead of being ignored
+    fn ignores(&self) -> &'static [&'static str] {
+        match self {
+            Command::Optimize => &[],
+            Command::Pareto(_) => &[
+                "--objective",
+                "--heat-up",
+                "--day",
+                "--search",
+                "--history",
+                "--results",
+                "--export",
+            ],
+            Command::Evaluate(_) => &[
+                "--optimization",
+                "--optimizer",
+                "--multi-start",
+                "--include",
+                "--exclude",
+                "--reflector-number",
+                "--reflector-type",
+                "--threads",
+                "--search",
+                "--history",
+                "--results",
+                "--export",
+                "--pareto",
+            ],
+        }
+    }
+}
+
+/// everything from the command line, with the files already loaded. see `USAGE`
+struct Args {
+    command: Command,
+    catalog: Catalog,
+    // the catalog's ovens after --include, --exclude and the shortcuts for them
+    choices: Choices,
+    env: Environment,
+    // --location and --time, the sun's position comes from these
+    sun: Option<(Location, DateTime)>,
+    day: Option<DaySimulation>,
+    heat_up: HeatUp,
+    objective: BuiltinObjective,
+    constraints: Constraints,
+    // the optimization file, with --optimizer, --multi-start, --search and --history on top
+    spec: OptimizationSpec,
+    results: usize,
+    threads: Option<usize>,
+    format: Format,
+    exports: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.peekable();
+        let evaluate = args.next_if_eq("evaluate").is_some();
+
+        let mut catalog = Catalog::builtin();
+        let mut env = Environment::default();
+        let mut location: Option<Location> = None;
+        let mut time: Option<DateTime> = None;
+        let mut day: Option<DaySimulation> = None;
+        let mut heat_up = HeatUp::default();
+        let mut objective = BuiltinObjective::default();
+        let mut constraints = Constraints::default();
+        let mut spec = OptimizationSpec::default();
+        let mut optimizer: Option<BuiltinOptimizer> = None;
+        let mut multi_start: Option<MultiStart> = None;
+        let mut search = false;
+        let mut history = false;
+        let mut pareto: Option<String> = None;
+        let mut results = 3;
+        let mut filters: Vec<Filter> = Vec::new();
+        let mut threads: Option<usize> = None;
+        let mut format = Format::Text;
+        let mut exports: Vec<String> = Vec::new();
+        let mut design: Option<Design> = None;
+        // every option used, to check them against the command
+        let mut given: Vec<String> = Vec::new();
+
+        fn loaded<T>(path: &str, result: Result<T, LoadError>) -> Result<T, String> {
+            result.map_err(|e| format!("{path}: {e}"))
+        }
+
+        while let Some(arg) = args.next() {
+            let mut value = |what: &str| args.next().ok_or_else(|| format!("{arg} needs {what}"));
+
+            match arg.as_str() {
+                "--help" | "-h" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                "--environment" => {
+                    let path = value("a file")?;
+                    env = loaded(&path, Environment::load(&path))?;
+                }
+                "--day" => {
+                    let path = value("a file")?;
+                    day = Some(loaded(&path, DaySimulation::load(&path))?);
+                }
+                "--heat-up" => {
+                    let path = value("a file")?;
+                    heat_up = loaded(&path, HeatUp::load(&path))?;
+                }
+                "--objective" => objective = value("an objective")?.parse()?,
+                "--constraints" => {
+                    let path = value("a file")?;
+                    constraints = loaded(&path, Constraints::load(&path))?;
+                }
+                "--optimization" => {
+                    let path = value("a file")?;
+                    spec = loaded(&path, OptimizationSpec::load(&path))?;
+                }
+                "--optimizer" => optimizer = Some(value("an optimizer")?.parse()?),
+                "--multi-start" => multi_start = Some(value("a number of starts")?.parse()?),
+                "--search" => search = true,
+                "--history" => history = true,
+                "--pareto" => pareto = Some(value("a file")?),
+                "--results" => {
+                    let n = value("a number")?;
+                    results = n
+                        .parse()
+                        .map_err(|_| format!("bad number of results \"{n}\""))?;
+                }
+                flag @ ("--include" | "--exclude") => {
+                    let filter: Filter = value("<category>=<name>")?.parse()?;
+                    filters.push(Filter {
+                        exclude: flag == "--exclude",
+                        ..filter
+                    });
+                }
+                flag @ ("--reflector-number" | "--reflector-type") => {
+                    let filter = format!("{}={}", flag.trim_start_matches("--"), value("a value")?);
+                    filters.push(filter.parse()?);
+                }
+                "--threads" => {
+                    let n = value("a number")?;
+                    threads = Some(
+                        n.parse()
+                            .map_err(|_| format!("bad number of threads \"{n}\""))?,
+                    );
+                }
+                "--format" => format = value("text, json or csv")?.parse()?,
+                "--export" => exports.push(value("a file")?),
+                "--location" => location = Some(value("a lat,lon")?.parse()?),
+                "--time" => time = Some(value("a time")?.parse()?),
+                option if option.starts_with("--") => {
+                    return Err(format!("unknown option {option}, see --help"));
+                }
+                path if evaluate => design = Some(loaded(path, Design::load(path))?),
+                path => catalog = loaded(path, Catalog::load(path))?,
+            }
+
+            if arg.starts_with("--") {
+                given.push(arg);
+            }
+        }
+
+        // these win over the optimization file, whichever order they're in
+        if let Some(optimizer) = optimizer {
+            spec.optimizer = optimizer;
+        }
+        if multi_start.is_some() {
+            spec.multi_start = multi_start;
+        }
+        if search && spec.search.is_none() {
+            spec.search = Some(GeneticSearch::default());
+        }
+        spec.history |= history;
+
+        let sun = match (location, time) {
+            (Some(location), Some(time)) => Some((location, time)),
+            (None, None) => None,
+            _ => return Err("--location and --time have to be used together".to_string()),
+        };
+
+        let command = match (evaluate, pareto) {
+            (true, _) => Command::Evaluate(Box::new(design.ok_or("evaluate needs a design file")?)),
+            (false, Some(path)) => Command::Pareto(path),
+            (false, None) => Command::Optimize,
+        };
+        if let Some(option) = given
+            .iter()
+            .find(|o| command.ignores().contains(&o.as_str()))
+        {
+            return Err(format!("{option} can't be used with {}", command.name()));
+        }
+        if matches!(command, Command::Evaluate(_)) && format == Format::Csv {
+            return Err("evaluate can only write text or json".to_string());
+        }
+        if matches!(command, Command::Pareto(_)) && format != Format::Text {
+            return Err("--pareto can only write text".to_string());
+        }
+
+        let mut choices = catalog.choices();
+        for filter in &filters {
+            choices.apply(filter)?;
+        }
+
+        Ok(Self {
+            command,
+            catalog,
+            choices,
+            env,
+            sun,
+            day,
+            heat_up,
+            objective,
+            constraints,
+            spec,
+            results,
+            threads,
+            format,
+            exports,
+        })
+    }
+}
 
 fn main() {
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|e| exit(&e));
+    let format = args.format;
+
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .unwrap_or_else(|e| exit(&e.to_string()));
+    }
+
+    // with a location and time the oven is aimed at the sun
+    let mut env = args.env;
+    if let Some((location, time)) = &args.sun {
+        env = env.at(location, time).aimed_at_sun();
+        note!(
+            format,
+            "sun at {time}: {:.2}° elevation, {:.2}° azimuth",
+            env.sun_angle.to_degrees(),
+            env.sun_azimuth.to_degrees()
+        );
+    }
+
+    if let Command::Evaluate(design) = &args.command {
+        run_evaluate(
+            design,
+            &env,
+            &args.objective,
+            &args.constraints,
+            &args.heat_up,
+            args.day.as_ref(),
+            format,
+        );
+        return;
+    }
+
+    // catalog validation already made sure these fit
+    for window in &args.catalog.windows {
+        if let (Ok(line), Ok(r_squared)) = (window.uw_line(), window.uw_r_squared()) {
+            note!(
+                format,
+                "{window}: T = {:.4} ln(Uw) {:+.4}, R² = {r_squared:.5}",
+                line.coefficient,
+                line.intercept
+            );
+        }
+    }
+
+    match &args.command {
+        Command::Pareto(path) => {
+            let starts = args.spec.starts();
+            let variants = args
+                .choices
+                .ovens()
+                .into_iter()
+                .flat_map(|oven| starts.iter().map(move |&init| (oven.clone(), init)))
+                .collect();
+            run_pareto(variants, &env, &args.constraints, &args.spec, path);
+        }
+        _ => run_optimize(&args, &env),
+    }
+}
+
+/// the best designs by `args.objective`, and everything that went wrong on the way
+fn run_optimize(args: &Args, env: &Environment) {
+    let format = args.format;
+    let results = args.results;
+    let variants = args.choices.ovens();
+    let starts = args.spec.starts();
     let all = Arc::new(Mutex::new(Vec::new()));
 
-    let variants = variants();
+    let pb = ProgressBar::new(variants.len() as u64);
 
-    let variant_count = variants.len();
-    let pb = ProgressBar::new(variant_count as u64);
+    let all: Vec<_> = if args.spec.search.is_some() {
+        let result =
+            args.choices
+                .search(env, &args.objective, &args.constraints, &args.spec, &|| {
+                    pb.inc(1)
+                });
+        pb.finish();
 
-    variants
-        .into_par_iter()
-        .for_each_with(all.clone(), |all, (oven, init)| {
-            let (best_design, score) = oven.best_design(init);
+        note!(
+            format,
+            "\nsearched for {} generations, optimized {} of {} variants ({} fewer, {:.1}% saved)",
+            result.generations,
+            result.evaluated(),
+            result.total,
+            result.saved(),
+            100. * result.saved() as f64 / result.total.max(1) as f64
+        );
+
+        result
+            .ovens
+            .into_iter()
+            .map(|(_, result)| {
+                let violations = args.constraints.violations(&result.best.design, env);
+                (result, violations)
+            })
+            .collect()
+    } else {
+        variants
+            .into_par_iter()
+            .for_each_with(all.clone(), |all, oven| {
+                let result =
+                    oven.multi_start(&starts, env, &args.objective, &args.constraints, &args.spec);
+                let violations = args.constraints.violations(&result.best.design, env);
+
+                all.lock().unwrap().push((result, violations));
+
+                pb.inc(1);
+            });
 
-            all.lock().unwrap().push((best_design, score));
+        pb.finish();
 
+        let all = all.lock().unwrap().drain(..).collect();
+        all
+    };
+    let variant_count = all.len();
+
+    if starts.len() > 1 {
+        let disagreed = all.iter().filter(|(result, _)| result.disagreed()).count();
+        let missed: u32 = all
+            .iter()
+            .map(|(result, _)| result.starts - result.agreed)
+            .sum();
+        let failed: u32 = all.iter().map(|(result, _)| result.failed).sum();
+        note!(
+            format,
+            "\n{} starts per variant, they disagreed on {disagreed} of {variant_count} variants \
+             ({:.1}% of starts didn't reach the best, {failed} failed)",
+            starts.len(),
+            100. * missed as f64 / (variant_count * starts.len()) as f64
+        );
+    }
+
+    // the best start of these failed too, so there's nothing to rank
+    let (failed, all): (Vec<_>, Vec<_>) = all
+        .into_iter()
+        .map(|(result, violations)| (result.best, violations))
+        .partition(|(best, _)| best.failed());
+    list_variants("failed to optimize", &failed, variant_count, format);
+
+    let suspicious: Vec<_> = all
+        .iter()
+        .filter(|(best, _)| best.status.suspicious())
+        .cloned()
+        .collect();
+    list_variants("may not have converged", &suspicious, variant_count, format);
+
+    let (mut all, infeasible): (Vec<_>, Vec<_>) = all
+        .into_iter()
+        .partition(|(_, violations)| violations.is_empty());
+    all.sort_by(|a, b| a.0.score.total_cmp(&b.0.score));
+
+    if !infeasible.is_empty() {
+        note!(
+            format,
+            "\n{} of {variant_count} variants can't meet the constraints:",
+            infeasible.len()
+        );
+        let broken = infeasible
+            .iter()
+            .flat_map(|(_, violations)| violations.iter().map(|v| v.limit))
+            .counts();
+        for (limit, count) in broken
+            .into_iter()
+            .sorted_by_key(|&(_, count)| Reverse(count))
+        {
+            note!(format, "  {limit}: {count}");
+        }
+    }
+
+    for path in &args.exports {
+        export(path, &all, &infeasible, &failed, env);
+        note!(format, "\nwrote {path}");
+    }
+
+    let best = all.iter().take(results).map(|(best, _)| best);
+    match format {
+        Format::Text => {
+            println!("\nBest {results} designs of {}:\n", all.len());
+            for best in best {
+                print_design(best, env, &args.heat_up, args.day.as_ref());
+            }
+        }
+        Format::Json => {
+            let rows: Vec<Row> = best
+                .enumerate()
+                .map(|(i, best)| row(Some(i), best, true, env))
+                .collect();
+            let json = serde_json::to_string_pretty(&rows).unwrap_or_else(|e| exit(&e.to_string()));
+            println!("{json}");
+        }
+        Format::Csv => {
+            let mut csv = csv::Writer::from_writer(std::io::stdout());
+            for (i, best) in best.enumerate() {
+                csv.serialize(row(Some(i), best, true, env))
+                    .unwrap_or_else(|e| exit(&e.to_string()));
+            }
+            csv.flush().unwrap_or_else(|e| exit(&e.to_string()));
+        }
+    }
+}
+
+/// every variant, the ranked ones first, then the ones outside the constraints, then the failed
+/// ones
+fn export(
+    path: &str,
+    ranked: &[(OptimizationResult, Vec<Violation>)],
+    infeasible: &[(OptimizationResult, Vec<Violation>)],
+    failed: &[(OptimizationResult, Vec<Violation>)],
+    env: &Environment,
+) {
+    let variants = ranked
+        .iter()
+        .enumerate()
+        .map(|(i, (best, _))| (Some(i), best, true))
+        .chain(infeasible.iter().map(|(best, _)| (None, best, false)))
+        .chain(
+            failed
+                .iter()
+                .map(|(best, violations)| (None, best, violations.is_empty())),
+        );
+
+    let written = if path.ends_with(".csv") {
+        csv::Writer::from_path(path)
+            .and_then(|mut csv| {
+                for (i, best, feasible) in variants {
+                    csv.serialize(row(i, best, feasible, env))?;
+                }
+                csv.flush()?;
+                Ok(())
+            })
+            .map_err(|e| e.to_string())
+    } else {
+        let exported: Vec<Exported> = variants
+            .map(|(i, best, feasible)| {
+                let design = &best.design;
+                Exported {
+                    rank: i.map(|i| i + 1),
+                    score: best.score,
+                    cost: design.total_cost(),
+                    cost_breakdown: design.cost_breakdown(),
+                    tio: design.predicted_tio(env).as_celsius(),
+                    performance_index: design.performance_index(env),
+                    feasible,
+                    status: best.status.to_string(),
+                    evaluations: best.evaluations,
+                    constraints: best.constraints.clone(),
+                    design: design.clone(),
+                }
+            })
+            .collect();
+        serde_json::to_string_pretty(&exported)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()))
+    };
+
+    written.unwrap_or_else(|e| exit(&format!("{path}: {e}")));
+}
+
+fn row(i: Option<usize>, best: &OptimizationResult, feasible: bool, env: &Environment) -> Row {
+    let design = &best.design;
+
+    Row {
+        rank: i.map(|i| i + 1),
+        score: best.score,
+        cost: design.total_cost(),
+        tio: design.predicted_tio(env).as_celsius(),
+        performance_index: design.performance_index(env),
+        absorber: design.absorber.to_string(),
+        window: design.window.to_string(),
+        outer_body: design.outer_body.to_string(),
+        inner_body: design.inner_body.to_string(),
+        insulator: design.insulator.to_string(),
+        reflective_material: design.reflectors.to_string(),
+        reflector_number: design.reflector_count,
+        reflector_type: format!("{:?}", design.reflector_type),
+        l_and_w_cm: design.l_and_w.cm(),
+        h_cm: design.h.cm(),
+        insulator_thickness_cm: design.insulator_thickness.cm(),
+        reflector_ml: design.reflector_ml,
+        feasible,
+        status: best.status.to_string(),
+        evaluations: best.evaluations,
+        margins: best.constraints.iter().join("; "),
+    }
+}
+
+fn print_design(
+    best: &OptimizationResult,
+    env: &Environment,
+    heat_up: &HeatUp,
+    day: Option<&DaySimulation>,
+) {
+    let design = &best.design;
+    println!("score: {}", best.score);
+    println!(
+        "optimizer: {} after {} evaluations",
+        best.status, best.evaluations
+    );
+    if let Some(history) = &best.history {
+        // only where it changed in the digits that get printed
+        println!(
+            "history: {}",
+            history
+                .iter()
+                .map(|(evaluation, score)| (evaluation, format!("{score:.4}")))
+                .dedup_by(|a, b| a.1 == b.1)
+                .map(|(evaluation, score)| format!("{evaluation}: {score}"))
+                .join(", ")
+        );
+    }
+    println!(
+        "cost based performance index: {}",
+        design.performance_index(env)
+    );
+    println!("{}", design.report(env));
+    println!("cost breakdown:\n{}", design.cost_breakdown());
+    println!(
+        "outside: {:.2} x {:.2} x {:.2} cm, {:.3} kg",
+        design.outer_width().cm(),
+        design.outer_width().cm(),
+        design.outer_height().cm(),
+        design.total_weight()
+    );
+
+    let other = match env.tio_method {
+        TioMethod::Trendline => TioMethod::EnergyBalance,
+        TioMethod::EnergyBalance => TioMethod::Trendline,
+    };
+    let balance = design.solve_tio(other, env);
+    println!(
+        "Temp ({other:?}): {:.4}°C ({} iterations{})\n",
+        balance.tio.as_celsius(),
+        balance.iterations,
+        if balance.converged {
+            ""
+        } else {
+            ", didn't converge"
+        }
+    );
+
+    let report = design.heat_up(heat_up, env);
+    match report.time_to_target {
+        _ if report.diverged => println!("the heat up simulation diverged\n"),
+        Some(seconds) => println!(
+            "reaches {:.0}°C in {:.1} minutes\n",
+            heat_up.target.as_celsius(),
+            seconds / 60.
+        ),
+        None => println!(
+            "doesn't reach {:.0}°C in {:.0} minutes\n",
+            heat_up.target.as_celsius(),
+            heat_up.max_seconds / 60.
+        ),
+    }
+
+    if let Some(sim) = day {
+        print_day(&design.simulate_day(sim, env), sim);
+    }
+}
+
+fn run_evaluate(
+    design: &Design,
+    env: &Environment,
+    objective: &dyn Objective,
+    constraints: &Constraints,
+    heat_up: &HeatUp,
+    day: Option<&DaySimulation>,
+    format: Format,
+) {
+    let evaluation = design.evaluate(env, objective, constraints, heat_up);
+
+    match format {
+        Format::Text => {}
+        Format::Json => {
+            let json =
+                serde_json::to_string_pretty(&evaluation).unwrap_or_else(|e| exit(&e.to_string()));
+            println!("{json}");
+            return;
+        }
+        Format::Csv => unreachable!("`Args::parse` doesn't allow csv for evaluate"),
+    }
+
+    println!("\n{}", design.report(env));
+    println!("cost breakdown:\n{}", evaluation.cost_breakdown);
+    println!("score: {}", evaluation.score);
+    println!(
+        "cost based performance index: {}",
+        evaluation.performance_index
+    );
+    println!(
+        "chamber: {:.4} L, outside: {:.2} x {:.2} x {:.2} cm, {:.3} kg",
+        evaluation.chamber_volume,
+        evaluation.outer_width,
+        evaluation.outer_width,
+        evaluation.outer_height,
+        evaluation.weight
+    );
+    println!(
+        "sunlight absorbed: {:.2} W, {:.3}x from the reflectors",
+        evaluation.solar_gain, evaluation.reflector_gain
+    );
+    println!(
+        "Temp (Trendline): {:.4}°C, Temp (EnergyBalance): {:.4}°C{}",
+        evaluation.trendline_tio,
+        evaluation.energy_balance_tio,
+        if evaluation.energy_balance_converged {
+            ""
+        } else {
+            " (didn't converge)"
+        }
+    );
+    match evaluation.time_to_target {
+        Some(seconds) => println!(
+            "reaches {:.0}°C in {:.1} minutes",
+            heat_up.target.as_celsius(),
+            seconds / 60.
+        ),
+        None => println!(
+            "doesn't reach {:.0}°C in {:.0} minutes",
+            heat_up.target.as_celsius(),
+            heat_up.max_seconds / 60.
+        ),
+    }
+
+    if evaluation.violations.is_empty() {
+        println!("inside the constraints\n");
+    } else {
+        println!("outside the constraints:");
+        for violation in &evaluation.violations {
+            println!("  {violation}");
+        }
+        println!();
+    }
+
+    if let Some(sim) = day {
+        print_day(&design.simulate_day(sim, env), sim);
+    }
+}
+
+fn run_pareto(
+    variants: Vec<(Oven, [f64; 3])>,
+    env: &Environment,
+    constraints: &Constraints,
+    spec: &OptimizationSpec,
+    path: &str,
+) {
+    let pb = ProgressBar::new(variants.len() as u64);
+
+    let points: Vec<ParetoPoint> = variants
+        .into_par_iter()
+        .flat_map_iter(|(oven, init)| {
+            let points = oven.tradeoff(init, env, BUDGETS, constraints, spec);
             pb.inc(1);
-        });
+
+            points
+        })
+        .collect();
 
     pb.finish();
 
-    let mut all = all.lock().unwrap();
-    all.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    let point_count = points.len();
+    let front = pareto_front(points, env, constraints, spec);
 
-    println!("\nBest 3 designs of {variant_count}:\n");
-    for (design, score) in all.iter().take(3) {
-        println!("score: {score}");
+    println!(
+        "\nPareto front, {} of {point_count} designs:\n",
+        front.len()
+    );
+    println!(
+        "{:>8}  {:>9}  {:>7}  {:>7}  design",
+        "cost", "temp", "h", "insul."
+    );
+    for point in &front {
+        let oven = &point.oven;
         println!(
-            "cost based performance index: {}",
-            (design.predicted_tio() - AMBIENT) / design.total_cost()
+            "{:>8}  {:>8.2}°C  {:>5.2}cm  {:>5.2}cm  {} / {} / {} in {} / {} {} x{} {:?}",
+            format!("${:.2}", point.cost),
+            point.tio,
+            point.h * 100.,
+            point.insulator_thickness * 100.,
+            oven.abs,
+            oven.window,
+            oven.insulator,
+            oven.outer_body,
+            oven.inner_body,
+            oven.reflective_material,
+            oven.reflector_number,
+            oven.reflector_type,
         );
-        println!("{}", design);
     }
+
+    let json = serde_json::to_string_pretty(&front).unwrap_or_else(|e| exit(&e.to_string()));
+    std::fs::write(path, json).unwrap_or_else(|e| exit(&format!("{path}: {e}")));
+    println!("\nwrote {path}");
+}
+
+/// how many of the variants ended up like `what`, and the first few of them
+fn list_variants(
+    what: &str,
+    variants: &[(OptimizationResult, Vec<Violation>)],
+    total: usize,
+    format: Format,
+) {
+    const LISTED: usize = 10;
+
+    if variants.is_empty() {
+        return;
+    }
+
+    note!(format, "\n{} of {total} variants {what}:", variants.len());
+    for (result, _) in variants.iter().take(LISTED) {
+        let design = &result.design;
+        let margins = if result.constraints.is_empty() {
+            String::new()
+        } else {
+            format!(", {} inside", result.constraints.iter().join(", "))
+        };
+        note!(
+            format,
+            "  {} / {} / {} in {} / {} {} x{} {:?}: {} after {} evaluations{margins}",
+            design.absorber,
+            design.window,
+            design.insulator,
+            design.outer_body,
+            design.inner_body,
+            design.reflectors,
+            design.reflector_count,
+            design.reflector_type,
+            result.status,
+            result.evaluations
+        );
+    }
+    if variants.len() > LISTED {
+        note!(format, "  and {} more", variants.len() - LISTED);
+    }
+}
+
+fn print_day(report: &DayReport, sim: &DaySimulation) {
+    if let Some(peak) = report.peak {
+        println!("peak: {:.2}°C at {}", peak.tio.as_celsius(), peak.time);
+    }
+    println!(
+        "above {:.0}°C for {:.2} hours",
+        sim.cooking_threshold.as_celsius(),
+        report.hours_above_threshold
+    );
+
+    for sample in &report.samples {
+        println!(
+            "  {:02}:{:02}  sun {:>6.2}°  {:>7.2} W/m^2  {:>7.2}°C",
+            sample.time.hour,
+            sample.time.minute,
+            sample.sun.elevation.to_degrees(),
+            sample.irradiance.value(),
+            sample.tio.as_celsius()
+        );
+    }
+    println!();
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{message}");
+    std::process::exit(1);
 }
@@ -0,0 +1,43 @@
+use cobyla::{minimize, FailStatus, Func, RhoBeg, SuccessStatus};
+
+use super::{CobylaSettings, Minimum, Optimizer, Problem, Status};
+
+/// constrained optimization by linear approximations, what the optimizer has always used
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Cobyla(pub CobylaSettings);
+
+impl Optimizer for Cobyla {
+    fn minimize(&self, problem: &Problem) -> Minimum {
+        let objective = |x: &[f64], _: &mut ()| (problem.objective)(x);
+        let constraints: Vec<_> = problem
+            .constraints
+            .iter()
+            .map(|constraint| move |x: &[f64], _: &mut ()| constraint(x))
+            .collect();
+
+        // the objective and the constraints have to be the same type for cobyla
+        let objective: &dyn Func<()> = &objective;
+        let constraints: Vec<&dyn Func<()>> = constraints
+            .iter()
+            .map(|constraint| constraint as &dyn Func<()>)
+            .collect();
+
+        let (status, x, y) = match minimize(
+            objective,
+            problem.init,
+            problem.bounds,
+            &constraints,
+            (),
+            self.0.max_evaluations as usize,
+            RhoBeg::All(self.0.rho_begin),
+            None,
+        ) {
+            Ok((SuccessStatus::MaxEvalReached, x, y)) => (Status::MaxEvaluations, x, y),
+            Ok((_, x, y)) => (Status::Converged, x, y),
+            Err((FailStatus::RoundoffLimited, x, y)) => (Status::RoundoffLimited, x, y),
+            Err((e, x, y)) => (Status::Failed(format!("{e:?}")), x, y),
+        };
+
+        Minimum { x, y, status }
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Minimum, Optimizer, Problem, Rng, Status};
+
+/// DE/rand/1/bin: a population spread over the bounds, where every member is challenged by a
+/// mix of itself and three others. slow, but it doesn't care where it starts so it's a good
+/// check on the local optimizers. the same seed always gives the same answer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct DifferentialEvolution {
+    pub population: u32,
+    pub generations: u32,
+    // how far along the difference between two members a mutation goes
+    pub differential_weight: f64,
+    // chance of each x coming from the mutation instead of the member
+    pub crossover: f64,
+    pub seed: u32,
+}
+
+impl Default for DifferentialEvolution {
+    fn default() -> Self {
+        Self {
+            population: 30,
+            generations: 200,
+            differential_weight: 0.7,
+            crossover: 0.9,
+            seed: 0,
+        }
+    }
+}
+
+impl Optimizer for DifferentialEvolution {
+    fn minimize(&self, problem: &Problem) -> Minimum {
+        let n = problem.init.len();
+        // needs itself and 3 others
+        let size = self.population.max(4) as usize;
+        let mut rng = Rng::new(self.seed as u64);
+
+        // the starting point is a member too, so it can only do better
+        let mut init = problem.init.to_vec();
+        problem.clamp(&mut init);
+        let mut members: Vec<Vec<f64>> = vec![init];
+        while members.len() < size {
+            let x = problem
+                .bounds
+                .iter()
+                .map(|&(min, max)| min + rng.next_f64() * (max - min))
+                .collect();
+            members.push(x);
+        }
+        let mut values: Vec<f64> = members.iter().map(|x| problem.penalized(x)).collect();
+
+        for _ in 0..self.generations {
+            for i in 0..size {
+                // three others, all different
+                let mut picked = [i; 3];
+                for k in 0..3 {
+                    let mut j = rng.below(size);
+                    while j == i || picked[..k].contains(&j) {
+                        j = rng.below(size);
+                    }
+                    picked[k] = j;
+                }
+                let [a, b, c] = picked.map(|j| &members[j]);
+
+                // at least one x always comes from the mutation
+                let forced = rng.below(n);
+                let mut trial: Vec<f64> = (0..n)
+                    .map(|d| {
+                        if d == forced || rng.next_f64() < self.crossover {
+                            a[d] + self.differential_weight * (b[d] - c[d])
+                        } else {
+                            members[i][d]
+                        }
+                    })
+                    .collect();
+                problem.clamp(&mut trial);
+
+                let y = problem.penalized(&trial);
+                if y <= values[i] {
+                    members[i] = trial;
+                    values[i] = y;
+                }
+            }
+        }
+
+        let best = (0..size)
+            .min_by(|&a, &b| values[a].total_cmp(&values[b]))
+            .expect("the population is never empty");
+        let x = members.swap_remove(best);
+        let y = (problem.objective)(&x);
+
+        // it always runs every generation, there's nothing to converge
+        Minimum {
+            x,
+            y,
+            status: Status::Finished,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{finds_known_minima, finds_rosenbrock, solve};
+
+    #[test]
+    fn known_minima() {
+        finds_known_minima(&DifferentialEvolution::default(), 1e-4);
+        finds_rosenbrock(&DifferentialEvolution::default(), 1e-4);
+    }
+
+    #[test]
+    fn same_seed_same_answer() {
+        let bowl = |x: &[f64]| (x[0] - 0.3).powi(2) + x[1].abs();
+        let run = |seed| {
+            let de = DifferentialEvolution {
+                generations: 20,
+                seed,
+                ..Default::default()
+            };
+            solve(&de, &bowl, &[], &[1., 1.], &[(-2., 2.), (-2., 2.)])
+        };
+
+        assert_eq!(run(3), run(3));
+        assert_eq!(run(3).status, Status::Finished);
+        assert_ne!(run(3).x, run(4).x);
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Minimum, Optimizer, Problem, Status};
+
+/// tries every point of an evenly spaced grid over the bounds, then zooms in on the best one
+/// (a step either side) and does it again `refinements` times. it can't miss a basin that's
+/// wider than a step, which is what makes it a useful check, but it's `steps^n` evaluations a
+/// pass
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct Grid {
+    // points along each x, including both bounds
+    pub steps: u32,
+    pub refinements: u32,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            steps: 11,
+            refinements: 4,
+        }
+    }
+}
+
+impl Optimizer for Grid {
+    fn minimize(&self, problem: &Problem) -> Minimum {
+        let n = problem.bounds.len();
+        let steps = self.steps.max(2) as usize;
+
+        let mut best = problem.init.to_vec();
+        problem.clamp(&mut best);
+        let mut best_y = problem.penalized(&best);
+        let mut bounds = problem.bounds.to_vec();
+
+        for _ in 0..=self.refinements {
+            let spacing: Vec<f64> = bounds
+                .iter()
+                .map(|(min, max)| (max - min) / (steps - 1) as f64)
+                .collect();
+
+            // counts through every point in base `steps`
+            let mut index = vec![0; n];
+            'grid: loop {
+                let x: Vec<f64> = (0..n)
+                    .map(|d| bounds[d].0 + spacing[d] * index[d] as f64)
+                    .collect();
+                let y = problem.penalized(&x);
+                if y < best_y {
+                    best = x;
+                    best_y = y;
+                }
+
+                for i in index.iter_mut() {
+                    *i += 1;
+                    if *i < steps {
+                        continue 'grid;
+                    }
+                    *i = 0;
+                }
+                break;
+            }
+
+            bounds = (0..n)
+                .map(|d| {
+                    let (min, max) = problem.bounds[d];
+                    (
+                        (best[d] - spacing[d]).max(min),
+                        (best[d] + spacing[d]).min(max),
+                    )
+                })
+                .collect();
+        }
+
+        let y = (problem.objective)(&best);
+        Minimum {
+            x: best,
+            y,
+            status: Status::Finished,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{finds_known_minima, finds_rosenbrock, solve};
+
+    #[test]
+    fn known_minima() {
+        finds_known_minima(&Grid::default(), 1e-3);
+    }
+
+    #[test]
+    fn runs_its_whole_budget() {
+        let bowl = |x: &[f64]| x[0].powi(2);
+        let minimum = solve(&Grid::default(), &bowl, &[], &[1.], &[(-1., 1.)]);
+
+        assert_eq!(minimum.status, Status::Finished);
+    }
+
+    /// the default grid zooms in on the best of its coarse points, which is in the wrong part of
+    /// rosenbrock's narrow valley, so it needs a finer first pass
+    #[test]
+    fn rosenbrock() {
+        let fine = Grid {
+            steps: 21,
+            refinements: 8,
+        };
+        finds_rosenbrock(&fine, 1e-3);
+    }
+}
@@ -0,0 +1,437 @@
+//! ways of finding the x inside some bounds with the lowest objective.
+//!
+//! cobyla handles the constraints itself, the pure-Rust ones add `PENALTY` for every unit a
+//! constraint is broken by, so they can also run where cobyla can't (wasm)
+
+#[cfg(not(target_arch = "wasm32"))]
+mod cobyla;
+mod differential_evolution;
+mod grid;
+mod nelder_mead;
+
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+use crate::files::{fraction, positive, LoadError};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::cobyla::Cobyla;
+pub use differential_evolution::DifferentialEvolution;
+pub use grid::Grid;
+pub use nelder_mead::NelderMead;
+
+/// per unit a constraint is broken by, squared
+const PENALTY: f64 = 1e6;
+
+/// a function of x, the objective or a constraint
+pub type Function<'a> = &'a dyn Fn(&[f64]) -> f64;
+
+/// what an `Optimizer` minimizes
+pub struct Problem<'a> {
+    pub objective: Function<'a>,
+    // each one is >= 0 when it's met
+    pub constraints: &'a [Function<'a>],
+    pub init: &'a [f64],
+    // (min, max) of each x
+    pub bounds: &'a [(f64, f64)],
+}
+
+impl Problem<'_> {
+    /// the objective plus `PENALTY` for the constraints x breaks, NaN counts as infinitely bad
+    pub fn penalized(&self, x: &[f64]) -> f64 {
+        let broken: f64 = self.constraints.iter().map(|c| c(x).min(0.).powi(2)).sum();
+        let y = (self.objective)(x) + broken * PENALTY;
+
+        if y.is_nan() {
+            f64::INFINITY
+        } else {
+            y
+        }
+    }
+
+    /// x moved back inside the bounds
+    pub fn clamp(&self, x: &mut [f64]) {
+        for (x, &(min, max)) in x.iter_mut().zip(self.bounds) {
+            *x = x.clamp(min, max);
+        }
+    }
+}
+
+pub trait Optimizer: Sync {
+    fn minimize(&self, problem: &Problem) -> Minimum;
+}
+
+/// the best x an `Optimizer` found, the objective there and how it got there
+#[derive(Debug, Clone, PartialEq)]
+pub struct Minimum {
+    pub x: Vec<f64>,
+    pub y: f64,
+    pub status: Status,
+}
+
+/// how an optimizer finished
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Converged,
+    // ran every generation or grid point it was given. fixed budget methods don't check for
+    // convergence, so this is as done as they get
+    Finished,
+    // ran out of evaluations first, probably close but not all the way there
+    MaxEvaluations,
+    // cobyla's steps got too small to make progress, and starting again from nearby ended up
+    // somewhere else. `Oven::best_design` counts it as converged when the restart agrees
+    RoundoffLimited,
+    // x is wherever it was when it gave up, don't trust it
+    Failed(String),
+}
+
+impl Status {
+    pub fn failed(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+
+    /// finished, but maybe not at the minimum
+    pub fn suspicious(&self) -> bool {
+        matches!(self, Self::MaxEvaluations | Self::RoundoffLimited)
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Converged => write!(f, "converged"),
+            Self::Finished => write!(f, "finished its budget"),
+            Self::MaxEvaluations => write!(f, "ran out of evaluations"),
+            Self::RoundoffLimited => write!(f, "limited by roundoff"),
+            Self::Failed(why) => write!(f, "failed ({why})"),
+        }
+    }
+}
+
+/// one of the built-in optimizers, so it can be picked from the CLI or a JSON file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub enum BuiltinOptimizer {
+    // falls back to `NelderMead` on wasm, which cobyla doesn't build for
+    Cobyla(CobylaSettings),
+    NelderMead(NelderMead),
+    DifferentialEvolution(DifferentialEvolution),
+    Grid(Grid),
+}
+
+/// `Cobyla`'s settings, kept outside of it so wasm can still read a JSON file that picks it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct CobylaSettings {
+    pub max_evaluations: u32,
+    // the first step, in the units of x
+    pub rho_begin: f64,
+}
+
+impl Default for CobylaSettings {
+    fn default() -> Self {
+        Self {
+            max_evaluations: 100_000,
+            rho_begin: 0.5,
+        }
+    }
+}
+
+impl Default for BuiltinOptimizer {
+    fn default() -> Self {
+        Self::Cobyla(CobylaSettings::default())
+    }
+}
+
+impl BuiltinOptimizer {
+    pub(crate) fn validate(&self) -> Result<(), LoadError> {
+        let name = "optimizer";
+        let at_least = |field: &str, min: u32, value: u32| {
+            if value >= min {
+                Ok(())
+            } else {
+                Err(LoadError::Invalid(format!(
+                    "{name}: {field} must be at least {min}, got {value}"
+                )))
+            }
+        };
+
+        match self {
+            Self::Cobyla(o) => {
+                at_least("max_evaluations", 1, o.max_evaluations)?;
+                positive(name, "rho_begin", o.rho_begin)
+            }
+            Self::NelderMead(o) => {
+                at_least("max_evaluations", 1, o.max_evaluations)?;
+                positive(name, "tolerance", o.tolerance)?;
+                positive(name, "initial_step", o.initial_step)
+            }
+            Self::DifferentialEvolution(o) => {
+                at_least("population", 4, o.population)?;
+                positive(name, "differential_weight", o.differential_weight)?;
+                fraction(name, "crossover", o.crossover)
+            }
+            Self::Grid(o) => at_least("steps", 2, o.steps),
+        }
+    }
+}
+
+impl Optimizer for BuiltinOptimizer {
+    fn minimize(&self, problem: &Problem) -> Minimum {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Cobyla(settings) => Cobyla(*settings).minimize(problem),
+            #[cfg(target_arch = "wasm32")]
+            Self::Cobyla(_) => NelderMead::default().minimize(problem),
+            Self::NelderMead(o) => o.minimize(problem),
+            Self::DifferentialEvolution(o) => o.minimize(problem),
+            Self::Grid(o) => o.minimize(problem),
+        }
+    }
+}
+
+/// "cobyla", "nelder-mead", "differential-evolution[:<seed>]" or "grid[:<steps>]"
+impl FromStr for BuiltinOptimizer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match s.split_once(':') {
+            Some((name, value)) => (name, Some(value)),
+            None => (s, None),
+        };
+        let number = |what: &str| {
+            value
+                .ok_or_else(|| format!("\"{name}\" needs a {what}"))?
+                .parse::<u32>()
+                .map_err(|_| format!("bad {what} in \"{s}\""))
+        };
+
+        match (name, value) {
+            ("cobyla", None) => Ok(Self::default()),
+            ("nelder-mead", None) => Ok(Self::NelderMead(NelderMead::default())),
+            ("differential-evolution", None) => {
+                Ok(Self::DifferentialEvolution(DifferentialEvolution::default()))
+            }
+            ("differential-evolution", Some(_)) => {
+                Ok(Self::DifferentialEvolution(DifferentialEvolution {
+                    seed: number("seed")?,
+                    ..Default::default()
+                }))
+            }
+            ("grid", None) => Ok(Self::Grid(Grid::default())),
+            ("grid", Some(_)) => match number("number of steps")? {
+                steps @ 2.. => Ok(Self::Grid(Grid {
+                    steps,
+                    ..Default::default()
+                })),
+                _ => Err(format!("\"{s}\" needs at least 2 steps")),
+            },
+            _ => Err(format!(
+                "unknown optimizer \"{s}\", expected cobyla, nelder-mead, \
+                 differential-evolution[:<seed>] or grid[:<steps>]"
+            )),
+        }
+    }
+}
+
+/// splitmix64, enough randomness for `DifferentialEvolution` and the same every run
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    /// in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_f64() * n as f64) as usize
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// minimizes `objective` from `init` inside `bounds`, with `constraints` if there are any
+    pub(super) fn solve(
+        optimizer: &dyn Optimizer,
+        objective: Function,
+        constraints: &[Function],
+        init: &[f64],
+        bounds: &[(f64, f64)],
+    ) -> Minimum {
+        optimizer.minimize(&Problem {
+            objective,
+            constraints,
+            init,
+            bounds,
+        })
+    }
+
+    fn near(minimum: &Minimum, x: &[f64], y: f64, tolerance: f64) {
+        let off = minimum
+            .x
+            .iter()
+            .zip(x)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0., f64::max);
+        assert!(
+            off <= tolerance && (minimum.y - y).abs() <= tolerance,
+            "ended up at {minimum:?}, should be {x:?} with {y}"
+        );
+    }
+
+    const SQUARE: [(f64, f64); 2] = [(-2., 2.), (-2., 2.)];
+
+    /// `optimizer` finds the minimum of a bowl inside the bounds, a bowl whose minimum is outside
+    /// the bounds (so the answer is on them) and a bowl cut off by a constraint, all within
+    /// `tolerance`
+    pub(super) fn finds_known_minima(optimizer: &dyn Optimizer, tolerance: f64) {
+        let square = SQUARE;
+
+        let bowl = |x: &[f64]| (x[0] - 1.).powi(2) + (x[1] + 0.5).powi(2);
+        near(
+            &solve(optimizer, &bowl, &[], &[1.5, 1.5], &square),
+            &[1., -0.5],
+            0.,
+            tolerance,
+        );
+
+        let outside = |x: &[f64]| (x[0] - 3.).powi(2) + (x[1] - 3.).powi(2);
+        near(
+            &solve(optimizer, &outside, &[], &[0.5, 0.5], &[(0., 2.), (0., 2.)]),
+            &[2., 2.],
+            2.,
+            tolerance,
+        );
+
+        // x + y >= 1 is what stops it, the closest point to 0 on that line is (0.5, 0.5)
+        let origin = |x: &[f64]| x[0] * x[0] + x[1] * x[1];
+        let line = |x: &[f64]| x[0] + x[1] - 1.;
+        let cut = solve(optimizer, &origin, &[&line], &[1.5, 1.], &square);
+        near(&cut, &[0.5, 0.5], 0.5, tolerance);
+        assert!(line(&cut.x) > -1e-5, "broke the constraint at {cut:?}");
+    }
+
+    /// `optimizer` follows rosenbrock's curved valley from the usual (-1.2, 1) to (1, 1)
+    pub(super) fn finds_rosenbrock(optimizer: &dyn Optimizer, tolerance: f64) {
+        let rosenbrock = |x: &[f64]| (1. - x[0]).powi(2) + 100. * (x[1] - x[0] * x[0]).powi(2);
+        near(
+            &solve(optimizer, &rosenbrock, &[], &[-1.2, 1.], &SQUARE),
+            &[1., 1.],
+            0.,
+            tolerance,
+        );
+    }
+
+    #[test]
+    fn penalized_and_clamped() {
+        let objective = |x: &[f64]| x[0];
+        let above = |x: &[f64]| x[0] - 1.;
+        let constraints: &[Function] = &[&above];
+        let problem = Problem {
+            objective: &objective,
+            constraints,
+            init: &[0.],
+            bounds: &[(-1., 3.)],
+        };
+
+        assert_eq!(problem.penalized(&[2.]), 2.);
+        assert_eq!(problem.penalized(&[0.5]), 0.5 + 0.25 * PENALTY);
+        assert_eq!(problem.penalized(&[f64::NAN]), f64::INFINITY);
+
+        let mut x = [5.];
+        problem.clamp(&mut x);
+        assert_eq!(x, [3.]);
+        let mut x = [-5.];
+        problem.clamp(&mut x);
+        assert_eq!(x, [-1.]);
+    }
+
+    /// every CLI name gives the optimizer it says, which survives going through an
+    /// optimization file
+    #[test]
+    fn optimizers_from_the_cli() {
+        let parsed = [
+            ("cobyla", BuiltinOptimizer::default()),
+            (
+                "nelder-mead",
+                BuiltinOptimizer::NelderMead(NelderMead::default()),
+            ),
+            (
+                "differential-evolution",
+                BuiltinOptimizer::DifferentialEvolution(DifferentialEvolution::default()),
+            ),
+            (
+                "differential-evolution:7",
+                BuiltinOptimizer::DifferentialEvolution(DifferentialEvolution {
+                    seed: 7,
+                    ..Default::default()
+                }),
+            ),
+            ("grid", BuiltinOptimizer::Grid(Grid::default())),
+            (
+                "grid:5",
+                BuiltinOptimizer::Grid(Grid {
+                    steps: 5,
+                    ..Default::default()
+                }),
+            ),
+        ];
+
+        for (s, expected) in parsed {
+            let optimizer: BuiltinOptimizer = s.parse().unwrap();
+            assert_eq!(optimizer, expected, "{s}");
+            assert!(optimizer.validate().is_ok(), "{s}");
+
+            let json = serde_json::to_string(&optimizer).unwrap();
+            assert_eq!(
+                serde_json::from_str::<BuiltinOptimizer>(&json).unwrap(),
+                optimizer,
+                "{json}"
+            );
+        }
+
+        for bad in [
+            "grid:1",
+            "grid:x",
+            "cobyla:3",
+            "simplex",
+            "differential-evolution:-1",
+        ] {
+            assert!(bad.parse::<BuiltinOptimizer>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn splitmix() {
+        // the first output of splitmix64 seeded with 0, from its reference implementation
+        assert_eq!(Rng::new(0).next_u64(), 0xE220_A839_7B1D_CDAF);
+
+        let mut rng = Rng::new(42);
+        let mut seen = [0; 5];
+        for _ in 0..1000 {
+            let x = rng.next_f64();
+            assert!((0. ..1.).contains(&x));
+            seen[rng.below(5)] += 1;
+        }
+        // each of the 5 should get about 200
+        assert!(seen.iter().all(|&n| (150..250).contains(&n)), "{seen:?}");
+    }
+}
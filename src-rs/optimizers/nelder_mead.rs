@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Minimum, Optimizer, Problem, Status};
+
+/// the downhill simplex, with points that step outside the bounds clamped back in. it restarts
+/// from where it ended up with a fresh simplex a few times, a collapsed simplex is the usual way
+/// it gets stuck
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct NelderMead {
+    // for each restart
+    pub max_evaluations: u32,
+    pub restarts: u32,
+    // stops when the simplex's values are all this close
+    pub tolerance: f64,
+    // size of the first simplex, as a fraction of each bound's range
+    pub initial_step: f64,
+}
+
+impl Default for NelderMead {
+    fn default() -> Self {
+        Self {
+            max_evaluations: 2_000,
+            restarts: 2,
+            tolerance: 1e-10,
+            initial_step: 0.1,
+        }
+    }
+}
+
+// the usual coefficients
+const REFLECT: f64 = 1.;
+const EXPAND: f64 = 2.;
+const CONTRACT: f64 = 0.5;
+const SHRINK: f64 = 0.5;
+
+impl NelderMead {
+    /// the best point and whether the simplex got within `tolerance`
+    fn run(&self, problem: &Problem, start: &[f64]) -> (Vec<f64>, bool) {
+        let n = start.len();
+        let f = |x: &[f64]| problem.penalized(x);
+        let moved = |from: &[f64], towards: &[f64], t: f64| {
+            let mut x: Vec<f64> = from
+                .iter()
+                .zip(towards)
+                .map(|(a, b)| a + t * (b - a))
+                .collect();
+            problem.clamp(&mut x);
+            x
+        };
+
+        let mut simplex: Vec<(Vec<f64>, f64)> = Vec::with_capacity(n + 1);
+        simplex.push((start.to_vec(), f(start)));
+        for i in 0..n {
+            let (min, max) = problem.bounds[i];
+            let step = (max - min) * self.initial_step;
+            let mut x = start.to_vec();
+            // step away from whichever bound is further
+            x[i] += if x[i] + step <= max { step } else { -step };
+            problem.clamp(&mut x);
+            let y = f(&x);
+            simplex.push((x, y));
+        }
+        let mut evaluations = n + 1;
+        let mut converged = false;
+
+        while evaluations < self.max_evaluations as usize {
+            simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let best = simplex[0].1;
+            let worst = simplex[n].1;
+            if (worst - best).abs() <= self.tolerance * (1. + best.abs()) {
+                converged = true;
+                break;
+            }
+
+            let centroid: Vec<f64> = (0..n)
+                .map(|i| simplex[..n].iter().map(|(x, _)| x[i]).sum::<f64>() / n as f64)
+                .collect();
+
+            let reflected = moved(&centroid, &simplex[n].0, -REFLECT);
+            let reflected_y = f(&reflected);
+            evaluations += 1;
+
+            if reflected_y < best {
+                let expanded = moved(&centroid, &simplex[n].0, -EXPAND);
+                let expanded_y = f(&expanded);
+                evaluations += 1;
+
+                simplex[n] = if expanded_y < reflected_y {
+                    (expanded, expanded_y)
+                } else {
+                    (reflected, reflected_y)
+                };
+            } else if reflected_y < simplex[n - 1].1 {
+                simplex[n] = (reflected, reflected_y);
+            } else {
+                // inside if the reflection was worse than the worst point, outside otherwise
+                let towards = if reflected_y < worst {
+                    &reflected
+                } else {
+                    &simplex[n].0
+                };
+                let contracted = moved(&centroid, towards, CONTRACT);
+                let contracted_y = f(&contracted);
+                evaluations += 1;
+
+                if contracted_y < reflected_y.min(worst) {
+                    simplex[n] = (contracted, contracted_y);
+                } else {
+                    let best = simplex[0].0.clone();
+                    for (x, y) in simplex.iter_mut().skip(1) {
+                        *x = moved(&best, x, SHRINK);
+                        *y = f(x);
+                    }
+                    evaluations += n;
+                }
+            }
+        }
+
+        let (x, _) = simplex
+            .into_iter()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("the simplex always has n + 1 points");
+
+        (x, converged)
+    }
+}
+
+impl Optimizer for NelderMead {
+    fn minimize(&self, problem: &Problem) -> Minimum {
+        let mut x = problem.init.to_vec();
+        problem.clamp(&mut x);
+
+        // only the last restart matters, the others are just getting it closer
+        let mut converged = false;
+        for _ in 0..=self.restarts {
+            (x, converged) = self.run(problem, &x);
+        }
+
+        let y = (problem.objective)(&x);
+        Minimum {
+            x,
+            y,
+            status: if converged {
+                Status::Converged
+            } else {
+                Status::MaxEvaluations
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizers::tests::{finds_known_minima, finds_rosenbrock};
+
+    #[test]
+    fn known_minima() {
+        finds_known_minima(&NelderMead::default(), 1e-4);
+        finds_rosenbrock(&NelderMead::default(), 1e-4);
+    }
+}
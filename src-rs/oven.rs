@@ -1,36 +1,84 @@
-#[cfg(not(target_arch = "wasm32"))]
-use cobyla::{minimize, Func, RhoBeg};
 use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
 use wasm_bindgen::prelude::*;
 
 use crate::{
-    Absorber, BodyMaterial, Design, Insulator, ReflectiveMaterial, ReflectorType, WindowMaterial,
+    multistart::AGREE, AbsorberSpec, BodySpec, Constraints, Design, Environment, Function,
+    InsulatorSpec, Margin, Minimum, Objective, ObjectiveChoice, OptimizationSpec, Optimizer,
+    Problem, ReflectiveSpec, ReflectorType, Status, WindowSpec,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[wasm_bindgen]
+#[wasm_bindgen(getter_with_clone)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
 pub struct Oven {
-    pub abs: Absorber,
-    pub window: WindowMaterial,
-    pub inner_body: BodyMaterial,
-    pub outer_body: BodyMaterial,
-    pub insulator: Insulator,
+    pub abs: AbsorberSpec,
+    pub window: WindowSpec,
+    pub inner_body: BodySpec,
+    pub outer_body: BodySpec,
+    pub insulator: InsulatorSpec,
     pub reflector_type: ReflectorType,
-    pub reflective_material: ReflectiveMaterial,
+    pub reflective_material: ReflectiveSpec,
     pub reflector_number: u8,
 }
 
-pub fn score(input: &[f64], oven: &mut Oven) -> f64 {
-    let design: Design = (&*oven, input).into();
+/// how far (as a fraction of the bounds) a roundoff limited x is moved before optimizing it
+/// again, to check it
+const NUDGE: f64 = 0.05;
+
+/// what `Oven::best_design` found, and how the optimizer got there
+#[derive(Debug, Clone)]
+pub struct OptimizationResult {
+    pub design: Design,
+    pub score: f64,
+    pub status: Status,
+    // of the objective
+    pub evaluations: u32,
+    // each active limit and how far inside it the design ended up
+    pub constraints: Vec<Margin>,
+    // (evaluation, score) every time the score got better, constraints aside. only kept when
+    // `spec.history` is on
+    pub history: Option<Vec<(u32, f64)>>,
+}
 
-    design.score()
+impl OptimizationResult {
+    /// the optimizer failed, or ended up somewhere that can't be scored
+    pub fn failed(&self) -> bool {
+        self.status.failed() || self.score.is_nan()
+    }
 }
 
 impl Oven {
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn best_design(self, mut init: [f64; 3]) -> (Design, f64) {
-        let nan = |x: &[f64], _oven: &mut Oven| {
+    /// the design `objective` likes most that stays inside `constraints`, found with
+    /// `spec.optimizer`. the optimizers can give up on a constraint they can't meet, so check
+    /// `Constraints::feasible` (or the margins in `constraints`) before trusting it
+    pub fn best_design(
+        self,
+        init: [f64; 3],
+        env: &Environment,
+        objective: &dyn Objective,
+        constraints: &Constraints,
+        spec: &OptimizationSpec,
+    ) -> OptimizationResult {
+        let design = |x: &[f64]| -> Design { (&self, x, spec.chamber_volume).into() };
+
+        let evaluations = Cell::new(0);
+        let history = RefCell::new(Vec::new());
+        let score = |x: &[f64]| {
+            let y = objective.score(&design(x), env);
+            evaluations.set(evaluations.get() + 1);
+
+            if spec.history {
+                let mut history = history.borrow_mut();
+                if history.last().map_or(!y.is_nan(), |&(_, best)| y < best) {
+                    history.push((evaluations.get(), y));
+                }
+            }
+
+            y
+        };
+
+        let nan = |x: &[f64]| {
             if x[0].is_nan() || x[1].is_nan() || x[2].is_nan() {
                 -1.
             } else {
@@ -38,32 +86,92 @@ impl Oven {
             }
         };
 
-        let cons: Vec<&dyn Func<Oven>> = vec![&nan];
-
-        let (x, y) = match minimize(
-            score,
-            &mut init,
-            &[(5e-2, 0.4), (0., 1.5), (1e-2, 3.)],
-            &cons,
-            self.clone(),
-            100_000,
-            RhoBeg::All(0.5),
-            None,
-        ) {
-            Ok((_, x, y)) => Ok((x, y)),
-            // found a better solution in < 100_000 iterations
-            Err((cobyla::FailStatus::RoundoffLimited, x, y)) => Ok((x, y)),
-            Err((e, _, _)) => Err(e),
+        // >= 0 when the limit is met
+        let limits: Vec<_> = constraints
+            .active()
+            .into_iter()
+            .map(|limit| move |x: &[f64]| constraints.margin(limit, &design(x), env))
+            .collect();
+
+        let mut cons: Vec<Function> = vec![&nan];
+        cons.extend(limits.iter().map(|limit| limit as Function));
+
+        let minimize = |init: &[f64]| {
+            spec.optimizer.minimize(&Problem {
+                objective: &score,
+                constraints: &cons,
+                init,
+                bounds: &spec.bounds(),
+            })
+        };
+        let mut minimum = minimize(&init);
+
+        // how far x is outside the constraints, then its score, smaller is better
+        let rank = |minimum: &Minimum| {
+            let broken: f64 = cons.iter().map(|c| c(&minimum.x).min(0.).powi(2)).sum();
+            let y = if minimum.y.is_nan() {
+                f64::INFINITY
+            } else {
+                minimum.y
+            };
+
+            (broken, y)
+        };
+
+        // cobyla stopping on roundoff can mean it's there or that it got stuck, so start it again
+        // from a bit towards the middle of the bounds. whichever ends up better is kept, and it
+        // only counts as converged when both come back to the same score
+        if minimum.status == Status::RoundoffLimited {
+            let nudged: Vec<f64> = minimum
+                .x
+                .iter()
+                .zip(spec.bounds())
+                .map(|(&x, (min, max))| {
+                    let step = NUDGE * (max - min);
+                    if x > (min + max) / 2. {
+                        x - step
+                    } else {
+                        x + step
+                    }
+                })
+                .collect();
+            let again = minimize(&nudged);
+            if !again.status.failed() {
+                let agree = (again.y - minimum.y).abs() <= AGREE * minimum.y.abs().max(1.);
+                if rank(&again) < rank(&minimum) {
+                    minimum = again;
+                }
+                if agree {
+                    minimum.status = Status::Converged;
+                }
+            }
         }
-        .unwrap();
+        let design = design(&minimum.x);
 
-        ((&self, &x[..]).into(), y)
+        OptimizationResult {
+            constraints: constraints
+                .active()
+                .into_iter()
+                .map(|limit| Margin {
+                    limit,
+                    margin: constraints.margin(limit, &design, env),
+                })
+                .collect(),
+            design,
+            score: minimum.y,
+            status: minimum.status,
+            evaluations: evaluations.get(),
+            history: spec.history.then(|| history.take()),
+        }
     }
 }
 
 #[wasm_bindgen]
 pub fn oven_from_json(oven: &str) -> Option<Oven> {
-    serde_json::from_str(oven).ok()
+    let mut oven: Oven = serde_json::from_str(oven).ok()?;
+    oven.window.fit_uw_line();
+
+    Some(oven)
 }
 
 /// returns (h, insulator_thickness, data specifified by GraphDataResponse)
@@ -72,19 +180,26 @@ pub fn graph_data(
     oven: &Oven,
     reflector_ml: f64,
     response_type: GraphDataResponse,
+    env: &Environment,
+    objective: &ObjectiveChoice,
+    spec: &OptimizationSpec,
 ) -> Vec<GraphData> {
+    // every cm inside the bounds
+    let cm = |(min, max): (f64, f64)| {
+        ((min * 100.).ceil() as i64..=(max * 100.).floor() as i64).map(|n| (n as f64) / 100.)
+    };
+
     let mut data = Vec::new();
-    // 0.05 <= h <= 0.4
-    for h in (5..=40).map(|n| (n as f64) / 100.) {
-        for insulator_thickness in (0..=150).map(|n| (n as f64) / 100.) {
+    for h in cm(spec.h) {
+        for insulator_thickness in cm(spec.insulator_thickness) {
             let input: &[f64] = &[h, insulator_thickness, reflector_ml];
-            let design: Design = (&*oven, input).into();
+            let design: Design = (oven, input, spec.chamber_volume).into();
 
             let z = match response_type {
-                GraphDataResponse::Tio => design.predicted_tio(),
+                GraphDataResponse::Tio => design.predicted_tio(env).as_celsius(),
                 GraphDataResponse::Cost => design.total_cost(),
-                GraphDataResponse::PerformanceIndex => design.performance_index(),
-                GraphDataResponse::Score => design.score(),
+                GraphDataResponse::PerformanceIndex => design.performance_index(env),
+                GraphDataResponse::Score => objective.score(&design, env),
             };
 
             data.push(GraphData {
@@ -0,0 +1,243 @@
+//! the cost vs temperature trade-off, instead of squashing both into one score.
+//!
+//! every oven is optimized for the hottest design under each of `BUDGETS`, and the front is every
+//! design that no other design beats on both cost and temperature
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Constraints, Design, Environment, MaxTempUnderBudget, Objective, OptimizationSpec, Oven, Volume,
+};
+
+/// $, spread over what ovens actually cost. every budget is a full optimization of every oven
+pub const BUDGETS: &[f64] = &[1., 1.5, 2., 3., 4., 5., 7., 10.];
+
+/// $
+const SAME_COST: f64 = 0.01;
+
+/// m (or multiples of l and w for reflector_ml), how far outside its bounds the optimizer can
+/// leave a design before it doesn't count
+const BOUNDS_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct ParetoPoint {
+    pub cost: f64,
+    // °C
+    pub tio: f64,
+    pub oven: Oven,
+    // m, the same inputs as `graph_data`
+    pub h: f64,
+    pub insulator_thickness: f64,
+    pub reflector_ml: f64,
+    // m^3
+    pub chamber_volume: Volume,
+}
+
+impl ParetoPoint {
+    pub fn new(design: &Design, oven: Oven, env: &Environment) -> Self {
+        Self {
+            cost: design.total_cost(),
+            tio: design.predicted_tio(env).as_celsius(),
+            oven,
+            h: design.h.value(),
+            insulator_thickness: design.insulator_thickness.value(),
+            reflector_ml: design.reflector_ml,
+            chamber_volume: design.volume_target,
+        }
+    }
+
+    pub fn design(&self) -> Design {
+        let input: &[f64] = &[self.h, self.insulator_thickness, self.reflector_ml];
+
+        (&self.oven, input, self.chamber_volume).into()
+    }
+
+    /// a real number for both axes, inside `spec`'s bounds and `constraints`
+    fn valid(&self, env: &Environment, constraints: &Constraints, spec: &OptimizationSpec) -> bool {
+        let inside = [self.h, self.insulator_thickness, self.reflector_ml]
+            .iter()
+            .zip(spec.bounds())
+            .all(|(&x, (min, max))| x >= min - BOUNDS_TOLERANCE && x <= max + BOUNDS_TOLERANCE);
+
+        self.cost.is_finite()
+            && self.tio.is_finite()
+            && inside
+            && constraints.feasible(&self.design(), env)
+    }
+}
+
+impl Oven {
+    /// the hottest design under each of `budgets`, each one starting from where the last one
+    /// ended up. designs that break `constraints` are left out
+    pub fn tradeoff(
+        &self,
+        init: [f64; 3],
+        env: &Environment,
+        budgets: &[f64],
+        constraints: &Constraints,
+        spec: &OptimizationSpec,
+    ) -> Vec<ParetoPoint> {
+        let mut init = init;
+
+        budgets
+            .iter()
+            .filter_map(|&budget| {
+                let objective: &dyn Objective = &MaxTempUnderBudget { budget };
+                let design = self
+                    .clone()
+                    .best_design(init, env, objective, constraints, spec)
+                    .design;
+                init = [
+                    design.h.value(),
+                    design.insulator_thickness.value(),
+                    design.reflector_ml,
+                ];
+
+                constraints
+                    .feasible(&design, env)
+                    .then(|| ParetoPoint::new(&design, self.clone(), env))
+            })
+            .collect()
+    }
+}
+
+/// the points nothing else beats on both cost and temperature, cheapest first. designs that
+/// aren't valid (NaN, outside `spec`'s bounds or breaking `constraints`) are dropped
+pub fn pareto_front(
+    mut points: Vec<ParetoPoint>,
+    env: &Environment,
+    constraints: &Constraints,
+    spec: &OptimizationSpec,
+) -> Vec<ParetoPoint> {
+    points.retain(|p| p.valid(env, constraints, spec));
+    // cheapest first, hottest first when they cost the same, so each point only has to beat the
+    // hottest one before it
+    points.sort_by(|a, b| a.cost.total_cmp(&b.cost).then(b.tio.total_cmp(&a.tio)));
+
+    let mut front: Vec<ParetoPoint> = Vec::new();
+    // what the cheapest point of the last front point's cluster cost, so a run of points a
+    // fraction of a cent apart can't keep pushing the cluster up in price
+    let mut cluster = f64::NEG_INFINITY;
+    for point in points {
+        match front.last_mut() {
+            Some(best) if point.tio <= best.tio => {}
+            // designs that hit the same budget come out a fraction of a cent apart, only keep
+            // the hottest
+            Some(best) if point.cost - cluster < SAME_COST => *best = point,
+            _ => {
+                cluster = point.cost;
+                front.push(point);
+            }
+        }
+    }
+
+    front
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Catalog, Filter};
+
+    /// every point is on the front or has a front point at least as hot that costs at most
+    /// `SAME_COST` more
+    fn assert_covered(front: &[ParetoPoint], points: &[&ParetoPoint]) {
+        for point in points {
+            assert!(
+                front
+                    .iter()
+                    .any(|f| f.tio >= point.tio && f.cost <= point.cost + SAME_COST),
+                "${:.3} -> {:.1}°C isn't on the front or covered by it",
+                point.cost,
+                point.tio
+            );
+        }
+    }
+
+    /// (cost, tio) points with an ordinary design behind them
+    fn points(costs_and_tios: &[(f64, f64)]) -> Vec<ParetoPoint> {
+        let oven = Catalog::builtin().choices().nth(0).unwrap();
+        let input: &[f64] = &[0.1, 0.1, 3.];
+        let design: Design = (&oven, input).into();
+
+        costs_and_tios
+            .iter()
+            .map(|&(cost, tio)| ParetoPoint {
+                cost,
+                tio,
+                ..ParetoPoint::new(&design, oven.clone(), &Environment::default())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_cost_doesnt_chain() {
+        let env = Environment::default();
+        let constraints = Constraints::default();
+        let spec = OptimizationSpec::default();
+        let points = points(&[
+            (1.000, 50.),
+            (1.008, 51.),
+            (1.016, 52.),
+            (1.020, 51.5),
+            (2.000, 49.),
+            (3.000, 60.),
+            (3.005, 60.),
+        ]);
+
+        let front = pareto_front(points.clone(), &env, &constraints, &spec);
+        let front_points: Vec<(f64, f64)> = front.iter().map(|p| (p.cost, p.tio)).collect();
+
+        // $1.008 is within a cent of where its cluster started, $1.016 isn't
+        assert_eq!(front_points, [(1.008, 51.), (1.016, 52.), (3.000, 60.)]);
+        assert_covered(&front, &points.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn nothing_beats_the_front() {
+        let env = Environment::default();
+        let constraints = Constraints::default();
+        let spec = OptimizationSpec::default();
+
+        let mut choices = Catalog::builtin().choices();
+        for filter in [
+            "absorber=Black Construction Paper",
+            "window=Double Mylar",
+            "insulator=R30 Fiberglass",
+            "outer-body=Cardboard",
+            "inner-body=Cardboard",
+            "reflective-material=Mirror Sheets",
+        ] {
+            choices.apply(&filter.parse::<Filter>().unwrap()).unwrap();
+        }
+        let points: Vec<ParetoPoint> = choices
+            .ovens()
+            .iter()
+            .flat_map(|oven| oven.tradeoff(spec.initials[0], &env, BUDGETS, &constraints, &spec))
+            .collect();
+
+        let front = pareto_front(points.clone(), &env, &constraints, &spec);
+        assert!(front.len() > 1);
+
+        let candidates: Vec<&ParetoPoint> = points
+            .iter()
+            .filter(|p| p.valid(&env, &constraints, &spec))
+            .collect();
+        assert_covered(&front, &candidates);
+        for point in &front {
+            let beaten_by = candidates.iter().find(|other| {
+                other.cost + SAME_COST <= point.cost && other.tio >= point.tio
+                    || other.cost <= point.cost && other.tio > point.tio
+            });
+            assert!(
+                beaten_by.is_none(),
+                "${:.2} -> {:.1}°C is beaten by ${:.2} -> {:.1}°C",
+                point.cost,
+                point.tio,
+                beaten_by.unwrap().cost,
+                beaten_by.unwrap().tio
+            );
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Design;
+use crate::{Area, Design, Length};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::wasm_bindgen;
 
@@ -25,13 +25,13 @@ impl ReflectorType {
         }
     }
 
-    pub fn area(&self, ml: f64, window: f64, n_reflectors: u8) -> f64 {
+    pub fn area(&self, ml: f64, window: Length, n_reflectors: u8) -> Area {
         let reflector_length = ml * window;
         match self {
             // reflector_length * reflector_width * n_reflectors
             Self::Rectangular => reflector_length * window * (n_reflectors as f64),
             Self::Trapezoidal => {
-                ((reflector_length).powi(2) - window.powi(2)) * (n_reflectors as f64) / 4.
+                (reflector_length * reflector_length - window * window) * (n_reflectors as f64) / 4.
             }
         }
     }
@@ -45,7 +45,7 @@ impl Design {
     pub fn gain(&self) -> f64 {
         self.reflector_type.calc_gain(
             self.reflector_count,
-            self.reflectors.reflectivity(),
+            self.reflectors.reflectivity,
             self.reflector_ml,
             self.alpha(),
         )
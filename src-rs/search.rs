@@ -0,0 +1,291 @@
+//! finding the best ovens without optimizing every one the catalog can make.
+//!
+//! a genetic algorithm picks the materials, each oven it comes up with still gets the usual
+//! continuous optimization (`Oven::multi_start`), and every oven is only ever optimized once
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    optimizers::Rng, Choices, Constraints, Environment, MultiStartResult, Objective,
+    OptimizationSpec, Oven,
+};
+
+/// how many of the best ovens have to stay the same for `patience` generations before it stops
+const TOP: usize = 5;
+
+/// an index into each of the `Choices`, in `Category` order
+type Genome = [usize; 8];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct GeneticSearch {
+    pub population: u32,
+    pub generations: u32,
+    // chance of each material being swapped for a random one
+    pub mutation: f64,
+    // generations without a change to the best few ovens before it gives up
+    pub patience: u32,
+    pub seed: u32,
+}
+
+impl Default for GeneticSearch {
+    fn default() -> Self {
+        Self {
+            population: 60,
+            generations: 40,
+            mutation: 0.15,
+            patience: 6,
+            seed: 0,
+        }
+    }
+}
+
+/// the ovens a search optimized, best first
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    // designs inside the constraints come before the ones that aren't
+    pub ovens: Vec<(Oven, MultiStartResult)>,
+    pub generations: u32,
+    // how many ovens the choices can make
+    pub total: usize,
+}
+
+impl SearchResult {
+    /// ovens optimized
+    pub fn evaluated(&self) -> usize {
+        self.ovens.len()
+    }
+
+    /// ovens exhaustive mode would've optimized that this didn't
+    pub fn saved(&self) -> usize {
+        self.total - self.evaluated()
+    }
+}
+
+impl Choices {
+    /// the best ovens these choices can make, according to `objective`, found by
+    /// `spec.search` (the default `GeneticSearch` if there isn't one). `evaluated` is called
+    /// after each oven is optimized
+    pub fn search(
+        &self,
+        env: &Environment,
+        objective: &dyn Objective,
+        constraints: &Constraints,
+        spec: &OptimizationSpec,
+        evaluated: &(dyn Fn() + Sync),
+    ) -> SearchResult {
+        let settings = spec.search.unwrap_or_default();
+        let sizes: Genome = self.lens();
+        let total = sizes.iter().product();
+        let starts = spec.starts();
+
+        // a category with nothing in it makes no ovens, and there's nothing to pick from it
+        if total == 0 {
+            return SearchResult {
+                ovens: Vec::new(),
+                generations: 0,
+                total,
+            };
+        }
+
+        let mut rng = Rng::new(settings.seed as u64);
+        let random = |rng: &mut Rng| sizes.map(|size| rng.below(size));
+
+        // every oven optimized so far, with whether it's inside the constraints
+        let mut seen: HashMap<Genome, (MultiStartResult, bool)> = HashMap::new();
+        // working first, then feasible, then the lowest score
+        let rank = |(result, feasible): &(MultiStartResult, bool)| {
+            (result.best.failed(), !feasible, result.best.score)
+        };
+        let better = |a: &(MultiStartResult, bool), b: &(MultiStartResult, bool)| {
+            let (a, b) = (rank(a), rank(b));
+            (a.0, a.1).cmp(&(b.0, b.1)).then(a.2.total_cmp(&b.2))
+        };
+        let top = |seen: &HashMap<Genome, (MultiStartResult, bool)>| -> Vec<Genome> {
+            let mut genomes: Vec<&Genome> = seen.keys().collect();
+            genomes.sort_by(|a, b| better(&seen[*a], &seen[*b]));
+            genomes.into_iter().take(TOP).copied().collect()
+        };
+
+        let size = (settings.population as usize).max(2);
+        let mut population: Vec<Genome> = (0..size).map(|_| random(&mut rng)).collect();
+        let mut best = Vec::new();
+        let mut stale = 0;
+        let mut generations = 0;
+
+        for _ in 0..settings.generations {
+            generations += 1;
+
+            let mut new: Vec<Genome> = population
+                .iter()
+                .filter(|genome| !seen.contains_key(*genome))
+                .copied()
+                .collect();
+            new.sort();
+            new.dedup();
+            let results: Vec<_> = new
+                .into_par_iter()
+                .map(|genome| {
+                    let result =
+                        self.oven(&genome)
+                            .multi_start(&starts, env, objective, constraints, spec);
+                    let feasible = constraints.feasible(&result.best.design, env);
+                    evaluated();
+
+                    (genome, (result, feasible))
+                })
+                .collect();
+            seen.extend(results);
+
+            let now = top(&seen);
+            if now == best {
+                stale += 1;
+            } else {
+                stale = 0;
+                best = now;
+            }
+            if stale >= settings.patience || seen.len() == total {
+                break;
+            }
+
+            // the best ever always make it to the next generation
+            let mut next: Vec<Genome> = best.iter().take(size / 10 + 1).copied().collect();
+            // tournaments of 2
+            let pick = |rng: &mut Rng| {
+                let (a, b) = (population[rng.below(size)], population[rng.below(size)]);
+                if better(&seen[&a], &seen[&b]).is_le() {
+                    a
+                } else {
+                    b
+                }
+            };
+            while next.len() < size {
+                let (a, b) = (pick(&mut rng), pick(&mut rng));
+
+                // a child nobody has tried yet is worth a few more mutations
+                let mut child = a;
+                for _ in 0..10 {
+                    child = std::array::from_fn(|i| {
+                        if rng.next_f64() < settings.mutation {
+                            rng.below(sizes[i])
+                        } else if rng.next_f64() < 0.5 {
+                            a[i]
+                        } else {
+                            b[i]
+                        }
+                    });
+                    if !seen.contains_key(&child) {
+                        break;
+                    }
+                }
+                next.push(child);
+            }
+            population = next;
+        }
+
+        let mut ovens: Vec<_> = seen.into_iter().collect();
+        ovens.sort_by(|(_, a), (_, b)| better(a, b));
+
+        SearchResult {
+            ovens: ovens
+                .into_iter()
+                .map(|(genome, (result, _))| (self.oven(&genome), result))
+                .collect(),
+            generations,
+            total,
+        }
+    }
+
+    pub(crate) fn oven(&self, genome: &Genome) -> Oven {
+        let [abs, window, outer_body, inner_body, insulator, reflector_type, reflective_material, reflector_number] =
+            *genome;
+
+        Oven {
+            abs: self.absorbers[abs].clone(),
+            window: self.windows[window].clone(),
+            outer_body: self.outer_bodies[outer_body].clone(),
+            inner_body: self.inner_bodies[inner_body].clone(),
+            insulator: self.insulators[insulator].clone(),
+            reflector_type: self.reflector_types[reflector_type],
+            reflective_material: self.reflective_materials[reflective_material].clone(),
+            reflector_number: self.reflector_numbers[reflector_number],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Catalog, Filter, GoalDistance};
+
+    /// few enough ovens to optimize every one of them and check the search against it
+    fn choices() -> Choices {
+        let mut choices = Catalog::builtin().choices();
+        for filter in [
+            "window=Double Mylar",
+            "outer-body=Cardboard",
+            "inner-body=Cardboard",
+            "reflector-type=Trapezoidal",
+            "reflective-material=Mirror Sheets,Silver Reflective Vinyl,S Reflect 2000",
+        ] {
+            choices.apply(&filter.parse::<Filter>().unwrap()).unwrap();
+        }
+
+        choices
+    }
+
+    #[test]
+    fn finds_the_exhaustive_best() {
+        let env = Environment::default();
+        let objective = GoalDistance::default();
+        let constraints = Constraints::default();
+        let spec = OptimizationSpec {
+            search: Some(GeneticSearch {
+                population: 12,
+                patience: 4,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let choices = choices();
+
+        let exhaustive = choices
+            .ovens()
+            .into_iter()
+            .map(|oven| {
+                let result =
+                    oven.multi_start(&spec.starts(), &env, &objective, &constraints, &spec);
+                (oven, result)
+            })
+            .min_by(|(_, a), (_, b)| a.best.score.total_cmp(&b.best.score))
+            .unwrap();
+
+        let result = choices.search(&env, &objective, &constraints, &spec, &|| {});
+        let (oven, best) = &result.ovens[0];
+
+        assert_eq!(result.total, choices.len());
+        assert_eq!(result.evaluated() + result.saved(), result.total);
+        assert!(result.saved() > 0, "it optimized every oven");
+        assert_eq!(format!("{oven:?}"), format!("{:?}", exhaustive.0));
+        assert_eq!(best.best.score, exhaustive.1.best.score);
+    }
+
+    #[test]
+    fn nothing_to_search() {
+        let mut choices = choices();
+        choices.absorbers.clear();
+
+        let result = choices.search(
+            &Environment::default(),
+            &GoalDistance::default(),
+            &Constraints::default(),
+            &OptimizationSpec::default(),
+            &|| {},
+        );
+        assert_eq!(result.total, 0);
+        assert_eq!(result.evaluated() + result.saved(), 0);
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{Design, Environment, Temperature, TemperatureDifference};
+
+// how `Design::solve_tio` finds the interior temperature
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[wasm_bindgen]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub enum TioMethod {
+    // fit a ln trendline through `tio_at_uw` at each of the window's tabulated Uw points (with
+    // radiation linearized at that point's temperature), then intersect it with the window's own
+    // Uw trendline
+    #[default]
+    Trendline,
+    // find the temperature where the heat balance is self consistent, with Uw and the full
+    // T^4 radiative loss evaluated at that temperature
+    EnergyBalance,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TioSolution {
+    pub tio: Temperature,
+    pub method: TioMethod,
+    /// 0 for the trendline method
+    pub iterations: usize,
+    pub converged: bool,
+    /// `tio_at_uw(uw_at(tio), tio) - tio`, should be ~0 for the energy balance method
+    pub residual: TemperatureDifference,
+}
+
+const TOLERANCE: f64 = 1e-6;
+const MAX_ITERATIONS: usize = 200;
+/// nothing made out of cardboard is getting hotter than this
+const MAX_TIO: Temperature = Temperature::celsius(2000.);
+
+impl Design {
+    /// the heat balance residual at a given interior temperature: positive means the oven would
+    /// keep heating up, negative means it would cool down
+    pub fn heat_balance(&self, tio: Temperature, env: &Environment) -> TemperatureDifference {
+        self.tio_at_uw(self.uw_at(tio, env), tio, env) - tio
+    }
+
+    pub fn solve_tio(&self, method: TioMethod, env: &Environment) -> TioSolution {
+        match method {
+            TioMethod::Trendline => {
+                let intersection = self.trendline_intersection(env);
+                let converged = intersection.is_ok();
+                let tio = intersection.unwrap_or(env.ambient);
+
+                TioSolution {
+                    tio,
+                    method,
+                    iterations: 0,
+                    converged,
+                    residual: self.heat_balance(tio, env),
+                }
+            }
+            TioMethod::EnergyBalance => self.solve_energy_balance(env),
+        }
+    }
+
+    fn solve_energy_balance(&self, env: &Environment) -> TioSolution {
+        let f = |t: f64| self.heat_balance(Temperature::celsius(t), env).value();
+
+        let (root, iterations, converged) =
+            find_root(f, env.ambient.as_celsius(), MAX_TIO.as_celsius());
+        let tio = Temperature::celsius(root);
+
+        TioSolution {
+            tio,
+            method: TioMethod::EnergyBalance,
+            iterations,
+            converged,
+            residual: self.heat_balance(tio, env),
+        }
+    }
+}
+
+/// bracketed root finding with the Illinois variant of regula falsi.
+///
+/// returns (root, iterations, converged). if `f` doesn't change sign over `[lo, hi]` the closer
+/// end is returned and `converged` is false
+fn find_root(f: impl Fn(f64) -> f64, mut lo: f64, mut hi: f64) -> (f64, usize, bool) {
+    let mut f_lo = f(lo);
+    let mut f_hi = f(hi);
+
+    if f_lo.is_nan() || f_hi.is_nan() || f_lo.signum() == f_hi.signum() {
+        let closest = if f_lo.abs() < f_hi.abs() { lo } else { hi };
+        return (closest, 0, false);
+    }
+
+    // which side was kept last iteration, for the Illinois halving
+    let mut side = 0;
+
+    for i in 1..=MAX_ITERATIONS {
+        let x = (lo * f_hi - hi * f_lo) / (f_hi - f_lo);
+        let f_x = f(x);
+
+        if f_x.abs() < TOLERANCE || (hi - lo).abs() < TOLERANCE {
+            return (x, i, true);
+        }
+
+        if f_x.signum() == f_hi.signum() {
+            hi = x;
+            f_hi = f_x;
+            if side == -1 {
+                f_lo /= 2.;
+            }
+            side = -1;
+        } else {
+            lo = x;
+            f_lo = f_x;
+            if side == 1 {
+                f_hi /= 2.;
+            }
+            side = 1;
+        }
+    }
+
+    ((lo + hi) / 2., MAX_ITERATIONS, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_known_roots() {
+        let (root, _, converged) = find_root(|x| x * x - 2., 0., 2.);
+        assert!(converged);
+        assert!((root - 2f64.sqrt()).abs() < 1e-6, "{root}");
+
+        // the dottie number, cos(x) = x
+        let (root, _, converged) = find_root(|x| x.cos() - x, 0., 1.);
+        assert!(converged);
+        assert!((root - 0.739_085_133_2).abs() < 1e-6, "{root}");
+
+        let (_, iterations, converged) = find_root(|x| x * x + 1., -1., 1.);
+        assert!(!converged);
+        assert_eq!(iterations, 0);
+    }
+
+    /// the example design ends up where heating and losses balance, hotter and it cools back down
+    #[test]
+    fn energy_balance_is_balanced() {
+        let design = Design::from_toml(include_str!("../design.toml")).unwrap();
+        let env = Environment::default();
+        let solution = design.solve_tio(TioMethod::EnergyBalance, &env);
+
+        assert!(solution.converged);
+        assert!(solution.residual.value().abs() < 1e-3, "{solution:?}");
+        assert!(solution.tio > env.ambient);
+
+        let below = solution.tio - TemperatureDifference::kelvin(1.);
+        let above = solution.tio + TemperatureDifference::kelvin(1.);
+        assert!(design.heat_balance(below, &env).value() > 0.);
+        assert!(design.heat_balance(above, &env).value() < 0.);
+    }
+}
@@ -0,0 +1,297 @@
+//! where the sun is, using NOAA's solar position algorithm
+//! (the one in their solar calculator spreadsheets, good to about a minute of arc)
+
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+use crate::Angle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct Location {
+    // degrees, north is positive
+    pub latitude: f64,
+    // degrees, east is positive
+    pub longitude: f64,
+}
+
+/// a local date and time, `utc_offset` is in hours (-7 for PDT)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct DateTime {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub utc_offset: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct SunPosition {
+    // above the horizon, negative when the sun has set. includes atmospheric refraction
+    pub elevation: Angle,
+    // clockwise from north, so 180° is due south
+    pub azimuth: Angle,
+}
+
+impl DateTime {
+    /// minutes since local midnight
+    pub fn minutes(&self) -> u32 {
+        self.hour * 60 + self.minute
+    }
+
+    /// a day that's in the calendar, a time on the clock and an offset some time zone uses
+    pub(crate) fn is_valid(&self) -> bool {
+        let leap = self.year % 4 == 0 && (self.year % 100 != 0 || self.year % 400 == 0);
+        let days = match self.month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        };
+
+        (1..=12).contains(&self.month)
+            && (1..=days).contains(&self.day)
+            && self.hour <= 23
+            && self.minute <= 59
+            && (-12. ..=14.).contains(&self.utc_offset)
+    }
+
+    /// julian day, including the time of day
+    fn julian_day(&self) -> f64 {
+        // Meeus, Astronomical Algorithms ch. 7
+        let (mut y, mut m) = (self.year as f64, self.month as f64);
+        if m <= 2. {
+            y -= 1.;
+            m += 12.;
+        }
+
+        let a = (y / 100.).floor();
+        let b = 2. - a + (a / 4.).floor();
+
+        let day = self.day as f64 + (self.minutes() as f64 / 60. - self.utc_offset) / 24.;
+
+        (365.25 * (y + 4716.)).floor() + (30.6001 * (m + 1.)).floor() + day + b - 1524.5
+    }
+}
+
+impl Location {
+    pub fn sun_position(&self, time: &DateTime) -> SunPosition {
+        let t = (time.julian_day() - 2451545.) / 36525.;
+
+        let mean_long = (280.46646 + t * (36000.76983 + t * 0.0003032)).rem_euclid(360.);
+        let mean_anomaly = 357.52911 + t * (35999.05029 - 0.0001537 * t);
+        let eccentricity = 0.016708634 - t * (0.000042037 + 0.0000001267 * t);
+
+        let m = mean_anomaly.to_radians();
+        let center = m.sin() * (1.914602 - t * (0.004817 + 0.000014 * t))
+            + (2. * m).sin() * (0.019993 - 0.000101 * t)
+            + (3. * m).sin() * 0.000289;
+
+        let omega = (125.04 - 1934.136 * t).to_radians();
+        let apparent_long = (mean_long + center - 0.00569 - 0.00478 * omega.sin()).to_radians();
+
+        let mean_obliquity =
+            23. + (26. + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001813))) / 60.) / 60.;
+        let obliquity = (mean_obliquity + 0.00256 * omega.cos()).to_radians();
+
+        let declination = (obliquity.sin() * apparent_long.sin()).asin();
+
+        // equation of time, in minutes
+        let y = (obliquity / 2.).tan().powi(2);
+        let l0 = mean_long.to_radians();
+        let eot = 4.
+            * (y * (2. * l0).sin() - 2. * eccentricity * m.sin()
+                + 4. * eccentricity * y * m.sin() * (2. * l0).cos()
+                - 0.5 * y * y * (4. * l0).sin()
+                - 1.25 * eccentricity * eccentricity * (2. * m).sin())
+            .to_degrees();
+
+        let true_solar_minutes = (time.minutes() as f64 + eot + 4. * self.longitude
+            - 60. * time.utc_offset)
+            .rem_euclid(1440.);
+        let hour_angle = (true_solar_minutes / 4. - 180.).to_radians();
+
+        let lat = self.latitude.to_radians();
+        let cos_zenith = (lat.sin() * declination.sin()
+            + lat.cos() * declination.cos() * hour_angle.cos())
+        .clamp(-1., 1.);
+        let zenith = cos_zenith.acos();
+
+        let azimuth = {
+            let cos_az = ((lat.sin() * cos_zenith - declination.sin())
+                / (lat.cos() * zenith.sin()))
+            .clamp(-1., 1.);
+            let az = cos_az.acos().to_degrees();
+
+            if hour_angle > 0. {
+                (az + 180.).rem_euclid(360.)
+            } else {
+                (540. - az).rem_euclid(360.)
+            }
+        };
+
+        let elevation = 90. - zenith.to_degrees();
+
+        SunPosition {
+            elevation: Angle::degrees(elevation + refraction(elevation)),
+            azimuth: Angle::degrees(azimuth),
+        }
+    }
+}
+
+/// NOAA's approximation of atmospheric refraction, in degrees
+fn refraction(elevation: f64) -> f64 {
+    let tan_e = elevation.to_radians().tan();
+
+    let arcseconds = if elevation > 85. {
+        0.
+    } else if elevation > 5. {
+        58.1 / tan_e - 0.07 / tan_e.powi(3) + 0.000086 / tan_e.powi(5)
+    } else if elevation > -0.575 {
+        1735.
+            + elevation * (-518.2 + elevation * (103.4 + elevation * (-12.79 + elevation * 0.711)))
+    } else {
+        -20.772 / tan_e
+    };
+
+    arcseconds / 3600.
+}
+
+/// "lat,lon" in degrees, e.g. "34.05,-118.25"
+impl FromStr for Location {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (lat, lon) = s
+            .split_once(',')
+            .ok_or_else(|| format!("expected \"latitude,longitude\", got \"{s}\""))?;
+
+        let latitude: f64 = lat
+            .trim()
+            .parse()
+            .map_err(|_| format!("bad latitude \"{lat}\""))?;
+        let longitude: f64 = lon
+            .trim()
+            .parse()
+            .map_err(|_| format!("bad longitude \"{lon}\""))?;
+
+        if !(-90. ..=90.).contains(&latitude) || !(-180. ..=180.).contains(&longitude) {
+            return Err(format!("\"{s}\" isn't a place on earth"));
+        }
+
+        Ok(Self {
+            latitude,
+            longitude,
+        })
+    }
+}
+
+/// "YYYY-MM-DDTHH:MM" followed by "Z" or a "+HH:MM"/"-HH:MM" offset, e.g. "2024-05-14T11:30-07:00"
+impl FromStr for DateTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || format!("expected a time like \"2024-05-14T11:30-07:00\", got \"{s}\"");
+
+        let (date, time) = s.split_once('T').ok_or_else(bad)?;
+
+        let mut date = date.split('-');
+        let year = date.next().and_then(|y| y.parse().ok()).ok_or_else(bad)?;
+        let month = date.next().and_then(|m| m.parse().ok()).ok_or_else(bad)?;
+        let day = date.next().and_then(|d| d.parse().ok()).ok_or_else(bad)?;
+
+        let (clock, utc_offset) = if let Some(clock) = time.strip_suffix('Z') {
+            (clock, 0.)
+        } else {
+            let split = time.rfind(['+', '-']).ok_or_else(bad)?;
+            let (clock, offset) = time.split_at(split);
+            let sign = if offset.starts_with('-') { -1. } else { 1. };
+            let (h, m) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+            let h: f64 = h.parse().map_err(|_| bad())?;
+            let m: f64 = m.parse().map_err(|_| bad())?;
+
+            (clock, sign * (h + m / 60.))
+        };
+
+        let (hour, minute) = clock.split_once(':').ok_or_else(bad)?;
+        let hour = hour.parse().map_err(|_| bad())?;
+        let minute = minute.parse().map_err(|_| bad())?;
+
+        let time = Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            utc_offset,
+        };
+
+        if !time.is_valid() {
+            return Err(bad());
+        }
+
+        Ok(time)
+    }
+}
+
+impl Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.utc_offset < 0. { '-' } else { '+' };
+        let offset = self.utc_offset.abs();
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}{sign}{:02}:{:02}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            offset.trunc() as u32,
+            (offset.fract() * 60.).round() as u32
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn julian_days() {
+        let j2000: DateTime = "2000-01-01T12:00Z".parse().unwrap();
+        assert_eq!(j2000.julian_day(), 2451545.);
+
+        // Meeus example 7.a, 1957 October 4.81 (sputnik)
+        let sputnik: DateTime = "1957-10-04T19:26Z".parse().unwrap();
+        assert!((sputnik.julian_day() - 2436116.31).abs() < 1. / 1440.);
+    }
+
+    /// the example in NREL's solar position algorithm paper (Reda and Andreas, 2004), which gets
+    /// zenith 50.11162° and azimuth 194.34024° in Golden, Colorado
+    #[test]
+    fn nrel_example() {
+        let golden = Location {
+            latitude: 39.742476,
+            longitude: -105.1786,
+        };
+        // 12:30:30, the half minute goes in the offset since there are no seconds
+        let time = DateTime {
+            year: 2003,
+            month: 10,
+            day: 17,
+            hour: 12,
+            minute: 30,
+            utc_offset: -7. - 0.5 / 60.,
+        };
+        let sun = golden.sun_position(&time);
+
+        let zenith = 90. - sun.elevation.to_degrees();
+        let azimuth = sun.azimuth.to_degrees();
+        assert!((zenith - 50.11162).abs() < 0.02, "zenith {zenith}");
+        assert!((azimuth - 194.34024).abs() < 0.02, "azimuth {azimuth}");
+    }
+}
@@ -0,0 +1,307 @@
+//! how long a design takes to heat up, not just how hot it eventually gets.
+//!
+//! the oven is treated as one lump with a single temperature: the sun heats the absorber, walls,
+//! air and food together, and they lose heat through the same walls and window as the steady
+//! state model. this integrates the same heat balance as `TioMethod::EnergyBalance`, so it levels
+//! off at that temperature
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    files::{self, fraction, non_negative, positive, LoadError},
+    Design, Environment, HeatCapacity, Oven, Temperature,
+};
+
+const AIR_DENSITY: f64 = 1.2;
+const AIR_SPECIFIC_HEAT: f64 = 1005.;
+
+/// the most a single RK4 step can be, in units of the oven's time constant. RK4 is stable up to
+/// ~2.8 for a decaying temperature, this leaves room for the loss growing with temperature
+const MAX_STEP: f64 = 1.;
+
+/// what heats up along with the air. the masses of the oven's parts come from the catalog's
+/// kg_per_m2 / kg_per_m3, these are what it takes to heat them. specific heats are J/(kg*K)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct ThermalMass {
+    // paper is about 1340
+    pub absorber_specific_heat: f64,
+    // the inner body
+    pub wall_specific_heat: f64,
+    pub insulator_specific_heat: f64,
+    // the insulation has a temperature gradient through it, so only part of it heats up with
+    // the chamber
+    pub insulator_fraction: f64,
+    // kg
+    pub food_mass: f64,
+    // ~3500 for wet food, ~1800 for dough
+    pub food_specific_heat: f64,
+}
+
+impl Default for ThermalMass {
+    fn default() -> Self {
+        Self {
+            absorber_specific_heat: 1340.,
+            wall_specific_heat: 1400.,
+            insulator_specific_heat: 840.,
+            insulator_fraction: 0.5,
+            food_mass: 0.,
+            food_specific_heat: 3500.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct HeatUp {
+    pub thermal_mass: ThermalMass,
+    pub target: Temperature,
+    pub step_seconds: f64,
+    // give up after this long
+    pub max_seconds: f64,
+}
+
+impl Default for HeatUp {
+    fn default() -> Self {
+        Self {
+            thermal_mass: ThermalMass::default(),
+            // 400F
+            target: Temperature::celsius(204.),
+            step_seconds: 5.,
+            max_seconds: 4. * 60. * 60.,
+        }
+    }
+}
+
+impl HeatUp {
+    pub fn from_json(s: &str) -> Result<Self, LoadError> {
+        let config: Self = files::from_json(s)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, LoadError> {
+        let config: Self = files::from_toml(s)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// picks the format from the file extension, anything that isn't `.toml` is read as JSON
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let config: Self = files::load(path)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), LoadError> {
+        let mass = &self.thermal_mass;
+
+        non_negative(
+            "thermal_mass",
+            "absorber_specific_heat",
+            mass.absorber_specific_heat,
+        )?;
+        non_negative(
+            "thermal_mass",
+            "wall_specific_heat",
+            mass.wall_specific_heat,
+        )?;
+        non_negative(
+            "thermal_mass",
+            "insulator_specific_heat",
+            mass.insulator_specific_heat,
+        )?;
+        fraction(
+            "thermal_mass",
+            "insulator_fraction",
+            mass.insulator_fraction,
+        )?;
+        non_negative("thermal_mass", "food_mass", mass.food_mass)?;
+        non_negative(
+            "thermal_mass",
+            "food_specific_heat",
+            mass.food_specific_heat,
+        )?;
+
+        positive("heat_up", "step_seconds", self.step_seconds)?;
+        positive("heat_up", "max_seconds", self.max_seconds)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct HeatUpReport {
+    // `None` if it never got there in `max_seconds`
+    pub time_to_target: Option<f64>,
+    pub heat_capacity: HeatCapacity,
+    // temperature once a minute, starting from ambient
+    pub curve: Vec<HeatUpSample>,
+    pub final_tio: Temperature,
+    // the temperature stopped being a number and the simulation gave up. shouldn't happen, the
+    // steps get split up for ovens that react faster than `step_seconds`
+    pub diverged: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+pub struct HeatUpSample {
+    pub seconds: f64,
+    pub tio: Temperature,
+}
+
+impl Design {
+    /// J/K of everything that heats up with the chamber, using the same areas and volumes as
+    /// `total_weight`
+    pub fn heat_capacity(&self, mass: &ThermalMass) -> HeatCapacity {
+        let absorber = self.absorber.mass(self.asb()) * mass.absorber_specific_heat;
+        let wall = self.inner_body.mass(self.asb()) * mass.wall_specific_heat;
+        let insulator = mass.insulator_fraction
+            * self.insulator.mass(self.insulator_volume())
+            * mass.insulator_specific_heat;
+
+        let air = AIR_DENSITY * AIR_SPECIFIC_HEAT * (self.aw() * self.h).value();
+        let food = mass.food_mass * mass.food_specific_heat;
+
+        HeatCapacity::joules_per_kelvin(absorber + wall + insulator + air + food)
+    }
+
+    /// integrates the oven's temperature from ambient with RK4. a light, badly insulated oven
+    /// settles in less than `step_seconds`, so each step is split up to stay under `MAX_STEP`
+    /// time constants
+    pub fn heat_up(&self, config: &HeatUp, env: &Environment) -> HeatUpReport {
+        let capacity = self.heat_capacity(&config.thermal_mass).value();
+        let gain = self.solar_gain(env).value();
+
+        // dT/dt in K/s
+        let rate = |t: f64| {
+            let tio = Temperature::celsius(t);
+            let loss = self.loss_conductance(self.uw_at(tio, env), tio, env) * (tio - env.ambient);
+
+            (gain - loss.value()) / capacity
+        };
+
+        let dt = config.step_seconds;
+        let target = config.target.as_celsius();
+
+        let mut t = env.ambient.as_celsius();
+        let mut seconds = 0.;
+        let mut time_to_target = (t >= target).then_some(0.);
+        let mut curve = vec![HeatUpSample {
+            seconds,
+            tio: env.ambient,
+        }];
+
+        let mut diverged = false;
+
+        'steps: while seconds < config.max_seconds {
+            // 1/time constant, how fast the rate changes with temperature around here
+            let stiffness = (rate(t + 1.) - rate(t)).abs();
+            let substeps = (dt * stiffness / MAX_STEP).ceil().clamp(1., 1e4);
+            let h = dt / substeps;
+
+            for _ in 0..substeps as usize {
+                let k1 = rate(t);
+                let k2 = rate(t + h / 2. * k1);
+                let k3 = rate(t + h / 2. * k2);
+                let k4 = rate(t + h * k3);
+                let next = t + h / 6. * (k1 + 2. * k2 + 2. * k3 + k4);
+
+                if !next.is_finite() {
+                    diverged = true;
+                    break 'steps;
+                }
+                if time_to_target.is_none() && next >= target {
+                    // interpolate inside the step
+                    time_to_target = Some(seconds + h * (target - t) / (next - t));
+                }
+
+                t = next;
+                seconds += h;
+            }
+
+            if (seconds / 60.).floor() > (curve.last().unwrap().seconds / 60.).floor() {
+                curve.push(HeatUpSample {
+                    seconds,
+                    tio: Temperature::celsius(t),
+                });
+            }
+        }
+
+        HeatUpReport {
+            time_to_target,
+            heat_capacity: HeatCapacity::joules_per_kelvin(capacity),
+            curve,
+            final_tio: Temperature::celsius(t),
+            diverged,
+        }
+    }
+}
+
+/// seconds until the design reaches `target_celsius` from ambient, with `food_mass` kg of food
+/// inside. undefined if it never gets there
+#[wasm_bindgen]
+pub fn time_to_target(
+    oven: &Oven,
+    h: f64,
+    insulator_thickness: f64,
+    reflector_ml: f64,
+    env: &Environment,
+    target_celsius: f64,
+    food_mass: f64,
+) -> Option<f64> {
+    let input: &[f64] = &[h, insulator_thickness, reflector_ml];
+    let design: Design = (oven, input).into();
+
+    let config = HeatUp {
+        target: Temperature::celsius(target_celsius),
+        thermal_mass: ThermalMass {
+            food_mass,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    design.heat_up(&config, env).time_to_target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Catalog, Filter, TioMethod};
+
+    /// cardboard with no insulation heats up in seconds, faster than a 5 s step can follow
+    #[test]
+    fn uninsulated_oven_settles() {
+        let env = Environment::default();
+        let mut choices = Catalog::builtin().choices();
+        choices
+            .apply(&"insulator=Newspaper".parse::<Filter>().unwrap())
+            .unwrap();
+        let oven = &choices.ovens()[0];
+
+        for h in [0.05, 0.4] {
+            let input: &[f64] = &[h, 0., 3.];
+            let design: Design = (oven, input).into();
+            let report = design.heat_up(&HeatUp::default(), &env);
+            let settled = design.solve_tio(TioMethod::EnergyBalance, &env).tio;
+
+            assert!(!report.diverged);
+            assert!(report.curve.iter().all(|s| s.tio.as_celsius().is_finite()));
+            assert!(
+                (report.final_tio - settled).value().abs() < 0.1,
+                "h = {h}: ended at {:?}, should settle at {:?}",
+                report.final_tio,
+                settled
+            );
+        }
+    }
+}
@@ -1,10 +1,47 @@
-#[derive(Debug, Clone)]
+use linreg::linear_regression;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct LNTrendline {
     pub coefficient: f64,
     pub intercept: f64,
 }
 
 impl LNTrendline {
+    /// least squares fit through (x, y) points, the same as a spreadsheet's logarithmic trendline
+    pub fn fit(points: &[(f64, f64)]) -> Result<Self, linreg::Error> {
+        let (xs, ys): (Vec<_>, Vec<_>) = points.iter().map(|&(x, y)| (x.ln(), y)).unzip();
+
+        let (a, b) = linear_regression(&xs, &ys)?;
+
+        Ok(Self {
+            coefficient: a,
+            intercept: b,
+        })
+    }
+
+    /// coefficient of determination of this line over `points`
+    pub fn r_squared(&self, points: &[(f64, f64)]) -> f64 {
+        let mean = points.iter().map(|p| p.1).sum::<f64>() / points.len() as f64;
+
+        let residual: f64 = points
+            .iter()
+            .map(|&(x, y)| (y - self.y_at(x)).powi(2))
+            .sum();
+        let total: f64 = points.iter().map(|p| (p.1 - mean).powi(2)).sum();
+
+        1. - residual / total
+    }
+
+    /// y = coefficient * ln(x) + intercept
+    pub fn y_at(&self, x: f64) -> f64 {
+        self.coefficient * x.ln() + self.intercept
+    }
+
+    /// x = e ^ ((y - intercept) / coefficient)
+    pub fn x_at(&self, y: f64) -> f64 {
+        ((y - self.intercept) / self.coefficient).exp()
+    }
+
     pub fn x_intercept(&self, other: &LNTrendline) -> f64 {
         // self.coefficient * ln(x) + self.intercept = other.coefficient * ln(x) + other.intercept
         // (sc - oc)(ln(x)) = oi - si
@@ -34,3 +71,54 @@ impl LNTrendline {
         self.coefficient * x_int.ln() + self.intercept
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// points right on y = 50 ln(x) + 10 give that line back, with r^2 = 1
+    #[test]
+    fn fits_an_exact_line() {
+        let points: Vec<(f64, f64)> = [1., 2., 5., 10., 20.]
+            .into_iter()
+            .map(|x| (x, 50. * f64::ln(x) + 10.))
+            .collect();
+        let line = LNTrendline::fit(&points).unwrap();
+
+        assert!((line.coefficient - 50.).abs() < 1e-9);
+        assert!((line.intercept - 10.).abs() < 1e-9);
+        assert!((line.r_squared(&points) - 1.).abs() < 1e-12);
+        assert!((line.x_at(line.y_at(7.)) - 7.).abs() < 1e-9);
+    }
+
+    /// least squares through 3 points that aren't on a line, worked out by hand in ln(x):
+    /// ln(x) = 0, 1, 2 and y = 0, 2, 1 gives a slope of 1/2 and an intercept of 1/2
+    #[test]
+    fn least_squares_by_hand() {
+        let e = std::f64::consts::E;
+        let points = [(1., 0.), (e, 2.), (e * e, 1.)];
+        let line = LNTrendline::fit(&points).unwrap();
+
+        assert!((line.coefficient - 0.5).abs() < 1e-12);
+        assert!((line.intercept - 0.5).abs() < 1e-12);
+        // residuals are -1/2, 1, -1/2 out of a total of 2
+        assert!((line.r_squared(&points) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn lines_cross() {
+        let a = LNTrendline {
+            coefficient: 2.,
+            intercept: 1.,
+        };
+        let b = LNTrendline {
+            coefficient: -1.,
+            intercept: 4.,
+        };
+
+        // 2 ln(x) + 1 = -ln(x) + 4 at ln(x) = 1
+        assert!((a.x_intercept(&b) - std::f64::consts::E).abs() < 1e-12);
+        assert!((a.y_intercept(&b) - 3.).abs() < 1e-12);
+        assert!((b.y_intercept(&a) - 3.).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,319 @@
+//! physical quantities, so that the compiler catches it when meters get added to centimeters.
+//!
+//! everything is stored in SI units (temperatures in Celsius), and only gets converted when it's
+//! created or printed
+
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+macro_rules! quantity {
+    ($($(#[$meta:meta])* $name:ident, $si:ident);+ $(;)?) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+            #[serde(transparent)]
+            pub struct $name(f64);
+
+            // by hand instead of derived, the derive claims `doc` as a helper attribute and then
+            // the doc comments passed in above don't compile. these show up as `number` in the
+            // generated typescript
+            #[cfg(not(target_arch = "wasm32"))]
+            impl specta::Type for $name {
+                fn inline(
+                    opts: specta::DefOpts,
+                    generics: &[specta::DataType],
+                ) -> Result<specta::DataType, specta::ExportError> {
+                    f64::inline(opts, generics)
+                }
+            }
+
+            impl $name {
+                pub const fn $si(value: f64) -> Self {
+                    Self(value)
+                }
+
+                pub const fn value(self) -> f64 {
+                    self.0
+                }
+
+                pub fn abs(self) -> Self {
+                    Self(self.0.abs())
+                }
+
+                pub fn is_nan(self) -> bool {
+                    self.0.is_nan()
+                }
+            }
+
+            impl Add for $name {
+                type Output = Self;
+
+                fn add(self, rhs: Self) -> Self {
+                    Self(self.0 + rhs.0)
+                }
+            }
+
+            impl Sub for $name {
+                type Output = Self;
+
+                fn sub(self, rhs: Self) -> Self {
+                    Self(self.0 - rhs.0)
+                }
+            }
+
+            impl Neg for $name {
+                type Output = Self;
+
+                fn neg(self) -> Self {
+                    Self(-self.0)
+                }
+            }
+
+            impl Mul<f64> for $name {
+                type Output = Self;
+
+                fn mul(self, rhs: f64) -> Self {
+                    Self(self.0 * rhs)
+                }
+            }
+
+            impl Mul<$name> for f64 {
+                type Output = $name;
+
+                fn mul(self, rhs: $name) -> $name {
+                    $name(self * rhs.0)
+                }
+            }
+
+            impl Div<f64> for $name {
+                type Output = Self;
+
+                fn div(self, rhs: f64) -> Self {
+                    Self(self.0 / rhs)
+                }
+            }
+
+            impl Div for $name {
+                type Output = f64;
+
+                fn div(self, rhs: Self) -> f64 {
+                    self.0 / rhs.0
+                }
+            }
+
+            impl std::iter::Sum for $name {
+                fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                    Self(iter.map(|q| q.0).sum())
+                }
+            }
+        )+
+    };
+}
+
+/// `$a * $b = $out` (and `$b * $a = $out`), plus the matching divisions
+macro_rules! product {
+    ($($a:ident * $b:ident = $out:ident);+ $(;)?) => {
+        $(
+            impl Mul<$b> for $a {
+                type Output = $out;
+
+                fn mul(self, rhs: $b) -> $out {
+                    $out(self.0 * rhs.0)
+                }
+            }
+
+            impl Div<$b> for $out {
+                type Output = $a;
+
+                fn div(self, rhs: $b) -> $a {
+                    $a(self.0 / rhs.0)
+                }
+            }
+
+            impl_commute!($a, $b, $out);
+        )+
+    };
+}
+
+/// skips the reversed impls when both sides are the same type, those already exist
+macro_rules! impl_commute {
+    (Length, Length, $out:ident) => {};
+    ($a:ident, $b:ident, $out:ident) => {
+        impl Mul<$a> for $b {
+            type Output = $out;
+
+            fn mul(self, rhs: $a) -> $out {
+                $out(self.0 * rhs.0)
+            }
+        }
+
+        impl Div<$a> for $out {
+            type Output = $b;
+
+            fn div(self, rhs: $a) -> $b {
+                $b(self.0 / rhs.0)
+            }
+        }
+    };
+}
+
+quantity!(
+    /// meters
+    Length, meters;
+    /// square meters
+    Area, square_meters;
+    /// cubic meters
+    Volume, cubic_meters;
+    /// a change in temperature, in kelvin (same size as a degree Celsius)
+    TemperatureDifference, kelvin;
+    /// W/(m*K)
+    Conductivity, watts_per_meter_kelvin;
+    /// R-value, (m^2*K)/W
+    ThermalResistance, square_meter_kelvin_per_watt;
+    /// U-value, W/(m^2*K)
+    UValue, watts_per_square_meter_kelvin;
+    /// W/K
+    ThermalConductance, watts_per_kelvin;
+    /// W/m^2
+    Irradiance, watts_per_square_meter;
+    /// W
+    Power, watts;
+    /// radians
+    Angle, radians;
+    /// J/K
+    HeatCapacity, joules_per_kelvin;
+);
+
+product!(
+    Length * Length = Area;
+    Area * Length = Volume;
+    UValue * Area = ThermalConductance;
+    Irradiance * Area = Power;
+    ThermalConductance * TemperatureDifference = Power;
+);
+
+impl Length {
+    pub const fn centimeters(cm: f64) -> Self {
+        Self(cm / 100.)
+    }
+
+    pub const fn inches(inches: f64) -> Self {
+        Self(inches * 0.0254)
+    }
+
+    pub fn cm(self) -> f64 {
+        self.0 * 100.
+    }
+}
+
+impl Area {
+    pub fn sqrt(self) -> Length {
+        Length(self.0.sqrt())
+    }
+}
+
+impl Volume {
+    pub const fn liters(l: f64) -> Self {
+        Self(l / 1000.)
+    }
+
+    pub fn as_liters(self) -> f64 {
+        self.0 * 1000.
+    }
+}
+
+impl Angle {
+    pub const fn degrees(degrees: f64) -> Self {
+        Self(degrees.to_radians())
+    }
+
+    pub fn to_degrees(self) -> f64 {
+        self.0.to_degrees()
+    }
+
+    pub fn sin(self) -> f64 {
+        self.0.sin()
+    }
+
+    pub fn cos(self) -> f64 {
+        self.0.cos()
+    }
+}
+
+impl Div<Conductivity> for Length {
+    type Output = ThermalResistance;
+
+    fn div(self, rhs: Conductivity) -> ThermalResistance {
+        ThermalResistance(self.0 / rhs.0)
+    }
+}
+
+impl ThermalResistance {
+    pub fn recip(self) -> UValue {
+        UValue(self.0.recip())
+    }
+}
+
+impl UValue {
+    pub fn recip(self) -> ThermalResistance {
+        ThermalResistance(self.0.recip())
+    }
+}
+
+/// an absolute temperature, stored in Celsius.
+///
+/// temperatures can't be added together, only shifted by a `TemperatureDifference`
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(specta::Type))]
+#[serde(transparent)]
+pub struct Temperature(f64);
+
+impl Temperature {
+    pub const fn celsius(c: f64) -> Self {
+        Self(c)
+    }
+
+    pub const fn kelvin(k: f64) -> Self {
+        Self(k - 273.15)
+    }
+
+    pub const fn fahrenheit(f: f64) -> Self {
+        Self((f - 32.) * 5. / 9.)
+    }
+
+    pub fn as_celsius(self) -> f64 {
+        self.0
+    }
+
+    pub fn as_kelvin(self) -> f64 {
+        self.0 + 273.15
+    }
+
+    pub fn is_nan(self) -> bool {
+        self.0.is_nan()
+    }
+}
+
+impl Sub for Temperature {
+    type Output = TemperatureDifference;
+
+    fn sub(self, rhs: Self) -> TemperatureDifference {
+        TemperatureDifference(self.0 - rhs.0)
+    }
+}
+
+impl Add<TemperatureDifference> for Temperature {
+    type Output = Self;
+
+    fn add(self, rhs: TemperatureDifference) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Sub<TemperatureDifference> for Temperature {
+    type Output = Self;
+
+    fn sub(self, rhs: TemperatureDifference) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
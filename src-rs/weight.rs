@@ -0,0 +1,37 @@
+use crate::Design;
+
+impl Design {
+    fn absorber_weight(&self) -> f64 {
+        self.absorber.mass(self.asb())
+    }
+
+    fn inner_body_weight(&self) -> f64 {
+        self.inner_body.mass(self.asb())
+    }
+
+    fn insulator_weight(&self) -> f64 {
+        self.insulator.mass(self.insulator_volume())
+    }
+
+    fn outer_body_weight(&self) -> f64 {
+        self.outer_body.mass(self.outer_body_area())
+    }
+
+    fn window_weight(&self) -> f64 {
+        self.window.mass(self.aw())
+    }
+
+    fn reflector_weight(&self) -> f64 {
+        self.reflectors.mass(self.reflector_area())
+    }
+
+    /// kg, using the same areas and volumes as `total_cost`
+    pub fn total_weight(&self) -> f64 {
+        self.absorber_weight()
+            + self.inner_body_weight()
+            + self.insulator_weight()
+            + self.outer_body_weight()
+            + self.window_weight()
+            + self.reflector_weight()
+    }
+}